@@ -0,0 +1,42 @@
+// Times a tight insert loop into an open addressed table, once wrapping
+// indices with % and once with a bitmask, which is what bucket_index() now
+// does for every Dictionary table. Run with cargo bench
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const CAPACITY: usize = 1 << 16;
+const INSERTS: usize = CAPACITY / 2;
+const ROUNDS: u32 = 50;
+
+// Inserts INSERTS keys with linear probing, wrapping each index with wrap
+fn insert_loop(wrap: impl Fn(usize) -> usize) -> Duration {
+    let mut total = Duration::ZERO;
+    for _ in 0..ROUNDS {
+        let mut table: Vec<Option<u64>> = vec![None; CAPACITY];
+        let start = Instant::now();
+        for i in 0..INSERTS as u64 {
+            // Spreads the keys the way a real hasher would
+            let hash = black_box(i.wrapping_mul(0x9E37_79B9_7F4A_7C15)) as usize;
+            let mut index = wrap(hash);
+            while table[index].is_some() {
+                index = wrap(index + 1);
+            }
+            table[index] = Some(i);
+        }
+        total += start.elapsed();
+        black_box(&table);
+    }
+    total / ROUNDS
+}
+
+fn main() {
+    let capacity = black_box(CAPACITY);
+    let modulo = insert_loop(|n| n % capacity);
+    let mask = insert_loop(|n| n & (capacity - 1));
+
+    println!("{} inserts into {} buckets, mean of {} rounds", INSERTS, CAPACITY, ROUNDS);
+    println!("modulo:  {:?}", modulo);
+    println!("bitmask: {:?}", mask);
+    println!("speedup: {:.2}x", modulo.as_secs_f64() / mask.as_secs_f64());
+}
@@ -0,0 +1,83 @@
+// Times lookup heavy workloads on a Dictionary with sequential and with
+// random keys. Run it once with cargo bench and once with
+// cargo bench --features robin-hood to compare the two ways of probing
+
+extern crate alloc;
+
+// The dictionary module isn't public, so the bench builds its own copy of it
+// with the same features the crate was built with
+#[allow(dead_code)]
+#[path = "../src/dictionary.rs"]
+mod dictionary;
+#[allow(dead_code)]
+#[path = "../src/fnv.rs"]
+mod fnv;
+#[allow(dead_code)]
+#[path = "../src/sorted.rs"]
+mod sorted;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use dictionary::Dictionary;
+
+const KEYS: u64 = 50_000;
+const LOOKUP_ROUNDS: u32 = 10;
+const ROUNDS: u32 = 10;
+
+// Spreads the keys out over the whole range of u64, like random keys would be
+fn scramble(n: u64) -> u64 {
+    let mut z = n.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Returns the mean time of filling a dict with present, and of looking up
+// every key in present and in missing LOOKUP_ROUNDS times over
+fn run(present: &[u64], missing: &[u64]) -> (Duration, Duration, Duration) {
+    let (mut insert, mut hits, mut misses) = (Duration::ZERO, Duration::ZERO, Duration::ZERO);
+    for _ in 0..ROUNDS {
+        let mut dict: Dictionary<u64, u64> = Dictionary::new();
+        let start = Instant::now();
+        for key in present {
+            dict.insert(*key, *key);
+        }
+        insert += start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..LOOKUP_ROUNDS {
+            for key in present {
+                black_box(dict.get(black_box(key)));
+            }
+        }
+        hits += start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..LOOKUP_ROUNDS {
+            for key in missing {
+                black_box(dict.get(black_box(key)));
+            }
+        }
+        misses += start.elapsed();
+    }
+    (insert / ROUNDS, hits / ROUNDS, misses / ROUNDS)
+}
+
+fn report(name: &str, present: &[u64], missing: &[u64]) {
+    let (insert, hits, misses) = run(present, missing);
+    println!("{:<10} insert: {:>10.3?}  hits: {:>10.3?}  misses: {:>10.3?}", name, insert, hits, misses);
+}
+
+fn main() {
+    let probing = if cfg!(feature = "robin-hood") { "Robin Hood" } else { "perturb" };
+    println!("{} probing, {} keys, {} lookups of each per round", probing, KEYS, LOOKUP_ROUNDS);
+
+    let sequential: Vec<u64> = (0..KEYS).collect();
+    let sequential_missing: Vec<u64> = (KEYS..2 * KEYS).collect();
+    report("sequential", &sequential, &sequential_missing);
+
+    let random: Vec<u64> = (0..KEYS).map(scramble).collect();
+    let random_missing: Vec<u64> = (KEYS..2 * KEYS).map(scramble).collect();
+    report("random", &random, &random_missing);
+}
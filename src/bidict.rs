@@ -0,0 +1,126 @@
+use alloc::vec::Vec;
+use core::hash::Hash;
+use core::iter::FromIterator;
+
+use crate::dictionary::Dictionary;
+
+/* forward maps every key to its value
+ *
+ * reverse maps every value back to its key
+ *
+ * This is a dict where every value is unique, so it can be looked up by
+ * either side. The two dicts always hold the same pairs, so both lookups
+ * take a single probe
+ */
+#[derive(Clone)]
+pub struct BiDict<K: Clone + Hash, V: Clone + Hash> {
+    forward: Dictionary<K, V>,
+    reverse: Dictionary<V, K>
+}
+
+#[allow(dead_code)]
+impl<K: Clone + Hash + PartialEq, V: Clone + Hash + PartialEq> BiDict<K, V> {
+    pub fn new() -> BiDict<K, V> {
+        BiDict {
+            forward: Dictionary::new(),
+            reverse: Dictionary::new()
+        }
+    }
+
+    // Pairs key with value. Any pair that already had key or value is
+    // removed first, so neither ends up mapped twice
+    pub fn insert(&mut self, key: K, value: V) {
+        self.remove_by_key(&key);
+        self.remove_by_value(&value);
+        self.forward.insert(key.clone(), value.clone());
+        self.reverse.insert(value, key);
+    }
+
+    // Pairs key with value only if neither is in the dict already, handing
+    // both back otherwise
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<(), (K, V)> {
+        if self.forward.contains(&key) || self.reverse.contains(&value) {
+            return Err((key, value));
+        }
+        self.forward.insert(key.clone(), value.clone());
+        self.reverse.insert(value, key);
+        Ok(())
+    }
+
+    pub fn get_by_key(&self, key: &K) -> Option<&V> {
+        self.forward.get_key_value(key).map(|e| e.1)
+    }
+
+    pub fn get_by_value(&self, value: &V) -> Option<&K> {
+        self.reverse.get_key_value(value).map(|e| e.1)
+    }
+
+    pub fn remove_by_key(&mut self, key: &K) -> Option<V> {
+        let value = self.forward.remove(key)?;
+        self.reverse.remove(&value);
+        Some(value)
+    }
+
+    pub fn remove_by_value(&mut self, value: &V) -> Option<K> {
+        let key = self.reverse.remove(value)?;
+        self.forward.remove(&key);
+        Some(key)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.forward.contains(key)
+    }
+
+    pub fn contains_value(&self, value: &V) -> bool {
+        self.reverse.contains(value)
+    }
+
+    pub fn size(&self) -> usize {
+        self.forward.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.forward.is_empty()
+    }
+
+    // Returns an iterator over (key, value) references
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.forward.iter()
+    }
+
+    pub fn keys(&self) -> Vec<&K> {
+        self.forward.keys()
+    }
+
+    pub fn values(&self) -> Vec<&V> {
+        self.reverse.keys()
+    }
+
+    // Swaps the two sides, so values become the keys
+    pub fn into_inverse(self) -> BiDict<V, K> {
+        BiDict {
+            forward: self.reverse,
+            reverse: self.forward
+        }
+    }
+
+    pub fn into_dictionary(self) -> Dictionary<K, V> {
+        self.forward
+    }
+}
+
+impl<K: Clone + Hash + PartialEq, V: Clone + Hash + PartialEq> Default for BiDict<K, V> {
+    fn default() -> BiDict<K, V> {
+        BiDict::new()
+    }
+}
+
+impl<K: Clone + Hash + PartialEq, V: Clone + Hash + PartialEq> FromIterator<(K, V)> for BiDict<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> BiDict<K, V> {
+        let mut dict = BiDict::new();
+        for (key, value) in iter {
+            dict.insert(key, value);
+        }
+        dict
+    }
+}
@@ -1,16 +1,67 @@
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
-use std::clone::Clone;
-use std::fmt;
-use std::fmt::Write;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::hash::{BuildHasher, Hash, Hasher};
+use core::iter::FromIterator;
+use core::marker::PhantomData;
+use core::clone::Clone;
+use core::cmp::Ordering;
+use core::fmt;
+use core::fmt::Write;
+use core::ops::{Index, RangeBounds};
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use crate::fnv::FnvHasher;
+use crate::sorted::SortedDictionary;
+
+// What dicts hash keys with unless told otherwise. Without std there is no
+// RandomState, so FNV-1a is used instead
+#[cfg(feature = "std")]
+pub type DefaultHashBuilder = std::collections::hash_map::RandomState;
+#[cfg(not(feature = "std"))]
+pub type DefaultHashBuilder = crate::fnv::FnvBuildHasher;
 
 #[derive(Copy, Clone)]
-enum Bucket<K: Clone, V: Clone> {
+pub(crate) enum Bucket<K: Clone, V: Clone> {
     Entry((K, V, usize, usize)),
     Empty,
     Tombstone
 }
 
+/* Steps from index to the next bucket in a probe sequence. The result is
+ * not wrapped into the table yet, the caller does that for its capacity
+ * Probing uses two numbers that are used in the calculation of each index: perturb and PERTURB_SHIFT
+ * perturb is used in the calculating of the "random" probing and is shifted to the right by PERTURB_SHIFT
+ * bits after every iteration in the probing
+ * Once perturb runs out the probing goes linear, since 5*index + 1 on its own
+ * can loop over a few buckets forever when the capacity isn't a power of two,
+ * as a SmallDict's can be
+ */
+pub(crate) fn probe_step(index: usize, perturb: &mut usize) -> usize {
+    *perturb >>= PERTURB_SHIFT;
+    if *perturb == 0 {
+        index + 1
+    } else {
+        (5*index) + 1 + *perturb
+    }
+}
+
+/* Wraps n into a table of capacity buckets. Every Dictionary table is a
+ * power of two, so this is a bitmask instead of a much slower division
+ */
+#[inline]
+pub(crate) fn bucket_index(n: usize, capacity: usize) -> usize {
+    debug_assert!(capacity.is_power_of_two());
+    n & (capacity - 1)
+}
+
+pub(crate) const PERTURB_SHIFT: u8 = 5;
+
 /* capacity is the number of objects the dict can hold, resizes when 
  *      it is at 2/3 capacity
  *      
@@ -21,258 +72,2531 @@ enum Bucket<K: Clone, V: Clone> {
  *      full of Bucket enums, which either encode an empty spot, a
  *      spot where an item was deleted, or an item
  *
+ * tombstone_limit is the most tombstones the table may hold after a
+ *      removal before it is compacted, usize::MAX means never
+ *
+ * resize_policy decides how much capacity changes by when the table
+ *      grows or shrinks
+ *
+ * load_thresholds is the (high, low) fraction of capacity that size has to
+ *      go over to grow the table or under to shrink it. None means the usual
+ *      2/3 and 1/3
+ *
+ * hash_builder makes the hasher every key is hashed with, DefaultHashBuilder
+ *      unless the dict was made with with_hasher()
+ *
+ * generation goes up every time an entry is added or removed or the table
+ *      is rebuilt, so callers can tell whether the dict changed between
+ *      two points. Overwriting a value does not count
+ *
  * This is meant to be a hashmap for keys that can be hashed 
  */
-pub struct Dictionary<K: Clone + Hash, V: Clone> {
+#[derive(Clone)]
+pub struct Dictionary<K: Clone + Hash, V: Clone, S = DefaultHashBuilder> {
     capacity: usize,
     size: usize,
-    table: Vec<Bucket<K, V>>
+    table: Vec<Bucket<K, V>>,
+    tombstone_limit: usize,
+    resize_policy: ResizePolicy,
+    load_thresholds: Option<(f64, f64)>,
+    hash_builder: S,
+    generation: u64
 }
 
+/* How a dict picks its new capacity when it has to grow or shrink
+ *
+ * Double doubles on growth and halves on shrink, which is the default
+ *
+ * GoldenRatio grows and shrinks by a factor of about 1.618
+ *
+ * FixedStep(n) grows and shrinks by exactly n buckets, for when memory is tight
+ *
+ * Factor(f) grows by a factor of f and shrinks by dividing by it, f must
+ * be greater than 1
+ *
+ * Custom(f) grows to f(capacity) and halves on shrink
+ *
+ * Whatever the policy, the table always ends up big enough to stay under
+ * the high load threshold, 2/3 full unless set otherwise. The capacity a
+ * policy picks is then rounded to a power of two, up when growing and down
+ * when shrinking, so GoldenRatio, FixedStep and Factor move in bigger steps
+ * than they would on their own
+ */
 #[allow(dead_code)]
-impl<K: Clone + Hash + PartialEq, V: Clone> Dictionary<K, V>{
-    pub fn new() -> Dictionary<K, V> {
-        Dictionary {
-            capacity: 8,
-            size: 0,
-            table: vec![Bucket::Empty; 8]
+#[derive(Debug, Clone, Copy, Default)]
+pub enum ResizePolicy {
+    #[default]
+    Double,
+    GoldenRatio,
+    FixedStep(usize),
+    Factor(f64),
+    Custom(fn(usize) -> usize)
+}
+
+impl ResizePolicy {
+    fn grow(&self, capacity: usize) -> usize {
+        match self {
+            ResizePolicy::Double => 2 * capacity,
+            ResizePolicy::GoldenRatio => scale_up(capacity, 1.618),
+            ResizePolicy::FixedStep(n) => capacity + n,
+            ResizePolicy::Factor(f) => scale_up(capacity, *f),
+            ResizePolicy::Custom(f) => f(capacity)
         }
     }
 
-    pub fn with_capacity(size: usize) -> Dictionary<K, V> {
-        if size == 0 {
-            panic!("Cannot create a zero-sized dict");
+    fn shrink(&self, capacity: usize) -> usize {
+        match self {
+            ResizePolicy::GoldenRatio => (capacity as f64 / 1.618) as usize,
+            ResizePolicy::FixedStep(n) => capacity.saturating_sub(*n),
+            ResizePolicy::Factor(f) => (capacity as f64 / f) as usize,
+            _ => capacity / 2
         }
+    }
+}
 
-        Dictionary {
-            capacity: size,
-            size: 0,
-            table: vec![Bucket::Empty; size]
-        }
+// Largest power of two that is at most n, or 0 if n is
+fn round_down_to_power_of_two(n: usize) -> usize {
+    match n {
+        0 => 0,
+        _ => 1 << (usize::BITS - 1 - n.leading_zeros())
     }
+}
 
-    /* Performs a lookup using almost the exact same algorithm as insertion
-     * Returns an Some(value) if the key exists, and None otherwise
-     * Probing uses two numbers that are used in the calculation of each index: perturb and PERTURB_SHIFT
-     * perturb is used in the calculating of the "random" probing and is shifted to the right by PERTURB_SHIFT
-     * bits after every iteration in the probing
-     */
-    fn lookup(&self, key: &K) -> Option<(K, V, usize)> { 
-        let key_hash: usize = self.get_hash(&key);
+// Multiplies capacity by factor, rounding up since f64::ceil() needs std
+fn scale_up(capacity: usize, factor: f64) -> usize {
+    let grown = capacity as f64 * factor;
+    let truncated = grown as usize;
+    if (truncated as f64) < grown { truncated + 1 } else { truncated }
+}
 
-        let mut index = (key_hash % self.capacity) as usize;
-        const PERTURB_SHIFT: u8 = 5;
-        let mut perturb: usize = key_hash;
+/* A snapshot of how full a dict's table is
+ *
+ * live_load is the fraction of buckets holding an entry, effective_load
+ *      also counts tombstones since they slow down probing just the same
+ *
+ * fragmentation_ratio is the fraction of non-empty buckets that are
+ *      tombstones, 0 when the table has neither entries nor tombstones
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LoadStats {
+    pub capacity: usize,
+    pub size: usize,
+    pub tombstones: usize,
+    pub live_load: f64,
+    pub effective_load: f64,
+    pub fragmentation_ratio: f64
+}
 
-        loop {
-            let current: Bucket<K, V> = self.table.get(index).unwrap().clone();
+impl fmt::Display for LoadStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "capacity: {}, size: {}, tombstones: {}, live load: {:.3}, effective load: {:.3}, fragmentation: {:.3}",
+               self.capacity, self.size, self.tombstones,
+               self.live_load, self.effective_load, self.fragmentation_ratio)
+    }
+}
 
-            match current {
-                Bucket::Entry(d) => {
-                    if d.0 == *key {
-                        break Some((d.0, d.1, index));
-                    } else {
-                        perturb >>= PERTURB_SHIFT;
-                        index = ((5*index) + 1 + perturb) % self.capacity as usize;
-                        continue;
-                    }
-                },
+// The counts and ratios from stats(), taken all at once for logging
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DictStats {
+    pub size: usize,
+    pub capacity: usize,
+    pub load_factor: f64,
+    pub tombstone_count: usize,
+    pub tombstone_ratio: f64
+}
 
-                Bucket::Tombstone => {
-                    perturb >>= PERTURB_SHIFT;
-                    index = ((5*index) + 1 + perturb) % self.capacity as usize;
-                    continue;
-                }, 
+impl fmt::Display for DictStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "size: {}, capacity: {}, load factor: {:.3}, tombstones: {}, tombstone ratio: {:.3}",
+               self.size, self.capacity, self.load_factor,
+               self.tombstone_count, self.tombstone_ratio)
+    }
+}
 
-                Bucket::Empty => {
-                    break None;
-                }
-            };
-        }
+/* Where an entry in the output of symmetric_merge() came from: only the
+ * left dict, only the right dict, or both of them
+ */
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeSource<A, B> {
+    Left(A),
+    Right(B),
+    Both(A, B)
+}
+
+/* What changed going from one dict to another, as found by diff()
+ *
+ * added holds the entries whose keys are only in the other dict
+ *
+ * removed holds the entries whose keys are only in the first dict
+ *
+ * modified holds the keys in both dicts whose values differ, along with the
+ *      old value and then the new one
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct DictDiff<'a, K, V> {
+    pub added: Vec<(&'a K, &'a V)>,
+    pub removed: Vec<(&'a K, &'a V)>,
+    pub modified: Vec<(&'a K, &'a V, &'a V)>
+}
+
+/* entries holds every entry a dict had when snapshot() was called
+ *
+ * The entries are shared behind an Arc, so cloning a snapshot is cheap no
+ * matter how big the dict was. Only the entries are kept, not the layout
+ * of the table they were in
+ */
+#[derive(Debug, Clone)]
+pub struct DictSnapshot<K, V> {
+    entries: Arc<Vec<(K, V)>>
+}
+
+#[allow(dead_code)]
+impl<K, V> DictSnapshot<K, V> {
+    pub fn size(&self) -> usize {
+        self.entries.len()
     }
+}
 
-    // Inserts new items without regard for size of the dict, it is separated from 
-    // the insert() function to prevent recursion on resizing. 
-    fn force_insert(&mut self, key: K, value: V, key_hash: usize) {
-        let mut index = (key_hash % self.capacity) as usize;
-        const PERTURB_SHIFT: u8 = 5;
-        let mut perturb: usize = key_hash; 
+/* One bucket of the table as handed out by iter_buckets()
+ *
+ * Entry holds references to the key and value along with the hash and
+ * index stored next to them, Empty and Tombstone are the holes between
+ */
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BucketView<'a, K, V> {
+    Entry { key: &'a K, value: &'a V, hash: usize, index: usize },
+    Empty,
+    Tombstone
+}
 
-        loop {
-            let current: Bucket<K, V> = self.table.get(index).unwrap().clone();
+/* Returned by the try_ constructors and try_get() in place of a panic
+ *
+ * ZeroSizedDict is when a dict would be made with no buckets
+ *
+ * VecLengthMismatch is when try_from_vecs() gets a different number of
+ * values than keys
+ *
+ * KeyNotFound is when try_get() is given a key that is not in the dict
+ *
+ * CapacityOverflow is when the table for the asked for capacity would be
+ * too big to allocate
+ */
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DictError {
+    ZeroSizedDict,
+    VecLengthMismatch { expected: usize, got: usize },
+    KeyNotFound,
+    CapacityOverflow
+}
 
-            match current {
-                Bucket::Entry(d) => {
-                    if d.0 == key {
-                        self.table[index] = Bucket::Entry((d.0, value, d.2, index));
-                        break;
-                    } else {
-                        perturb >>= PERTURB_SHIFT;
-                        index = ((5*index) + 1 + perturb) % self.capacity as usize;
-                        continue
-                    }
-                },
+impl fmt::Display for DictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DictError::ZeroSizedDict => write!(f, "Cannot create a zero-sized dict"),
+            DictError::VecLengthMismatch { expected, got } =>
+                write!(f, "Differently sized vecs: expected {} values but got {}", expected, got),
+            DictError::KeyNotFound => write!(f, "Key does not exist"),
+            DictError::CapacityOverflow => write!(f, "Capacity is too big to allocate")
+        }
+    }
+}
 
-                _ => {
-                    self.table[index] = Bucket::Entry((key, value, key_hash, index));
-                    break;
-                }
-            };
+/* Returned when parsing a dict from a "key:value,key:value" string fails
+ *
+ * MissingSeparator is when a pair has no ':' between its key and value
+ *
+ * EmptyKey is when a pair has nothing before its ':'
+ */
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseDictError {
+    MissingSeparator { pair: String },
+    EmptyKey { pair: String }
+}
+
+impl fmt::Display for ParseDictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseDictError::MissingSeparator { pair } => write!(f, "Pair \"{}\" has no ':'", pair),
+            ParseDictError::EmptyKey { pair } => write!(f, "Pair \"{}\" has an empty key", pair)
         }
     }
+}
 
-    // Empties the table and makes a table twice the size, then reinserts all the entries
-    fn resize(&mut self, new_capacity: usize) {
-        self.capacity = new_capacity;
-        let _table = self.table.clone();
-        self.table = vec![Bucket::Empty; self.capacity];
-        for entry in _table.iter() {    
-            if let Bucket::Entry(d) = entry.clone() {
-                self.force_insert(d.0, d.1, d.2);
-            }
+// Returned by try_invert() when two keys share a value, so the dict
+// cannot be flipped without losing one of them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvertError;
+
+impl fmt::Display for InvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Two keys share the same value")
+    }
+}
+
+// Returned by try_reserve() when the table could not be grown
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AllocationError;
+
+impl fmt::Display for AllocationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Could not allocate a big enough table")
+    }
+}
+
+/* A broken invariant found by verify_integrity()
+ *
+ * SizeCountMismatch is when size does not match the number of entries
+ *
+ * HashMismatch is when an entry's stored hash is not the hash of its key
+ *
+ * IndexMismatch is when an entry's stored index is not the bucket it is in
+ *
+ * CapacityMismatch is when capacity does not match the length of the table
+ */
+#[allow(dead_code, clippy::enum_variant_names)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntegrityError {
+    SizeCountMismatch { stored: usize, actual: usize },
+    HashMismatch { key_repr: String, stored_hash: usize, computed_hash: usize },
+    IndexMismatch { stored_index: usize, actual_index: usize },
+    CapacityMismatch { stored: usize, table_len: usize }
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityError::SizeCountMismatch { stored, actual } =>
+                write!(f, "size is {} but the table holds {} entries", stored, actual),
+            IntegrityError::HashMismatch { key_repr, stored_hash, computed_hash } =>
+                write!(f, "key {} has stored hash {} but hashes to {}", key_repr, stored_hash, computed_hash),
+            IntegrityError::IndexMismatch { stored_index, actual_index } =>
+                write!(f, "entry in bucket {} has stored index {}", actual_index, stored_index),
+            IntegrityError::CapacityMismatch { stored, table_len } =>
+                write!(f, "capacity is {} but the table has {} buckets", stored, table_len)
         }
     }
+}
 
-    //Checks if a resize is needed before inserting the new item, resizes if needed
-    pub fn insert(&mut self, key: K, value: V) {
-        self.size += 1;
-        if 2 * (self.capacity/3) < self.size { // Double capacity if 2/3 full or more
-            self.resize(2 * self.capacity);
+#[allow(dead_code)]
+impl<K: Clone + Hash + PartialEq, V: Clone> Dictionary<K, V> {
+    pub fn new() -> Dictionary<K, V> {
+        Dictionary::with_capacity_and_hasher(8, DefaultHashBuilder::default())
+    }
+
+    pub fn with_capacity(size: usize) -> Dictionary<K, V> {
+        Dictionary::with_capacity_and_hasher(size, DefaultHashBuilder::default())
+    }
+
+    pub fn try_with_capacity(size: usize) -> Result<Dictionary<K, V>, DictError> {
+        Dictionary::try_with_capacity_and_hasher(size, DefaultHashBuilder::default())
+    }
+
+    // Smallest power of two capacity that holds size entries while staying
+    // under 2/3 full
+    pub fn capacity_for(size: usize) -> usize {
+        let mut capacity = (3 * size) / 2 + 1;
+        while 2 * (capacity/3) < size {
+            capacity += 1;
         }
-        let hash = self.get_hash(&key);
-        self.force_insert(key, value, hash);
+        capacity.next_power_of_two()
+    }
+
+    // Panics if the vectors are different sizes or empty
+    pub fn from_vecs(key_vec: Vec<K>, value_vec: Vec<V>) -> Dictionary<K, V> {
+        Dictionary::try_from_vecs(key_vec, value_vec).unwrap_or_else(|e| panic!("{}", e))
     }
 
-    //Returns a Result::Err if the vectors are different sizes
-    pub fn from_vecs(mut key_vec: Vec<K>, mut value_vec: Vec<V>) -> Dictionary<K, V> {
+    //Returns a Result::Err if the vectors are different sizes or empty
+    pub fn try_from_vecs(mut key_vec: Vec<K>, mut value_vec: Vec<V>) -> Result<Dictionary<K, V>, DictError> {
         if key_vec.len() != value_vec.len() {
-            panic!("Differently sized vecs");
+            Err(DictError::VecLengthMismatch { expected: key_vec.len(), got: value_vec.len() })
         } else if key_vec.is_empty() {
-            panic!("Cannot create a zero-sized dict");
+            Err(DictError::ZeroSizedDict)
         } else {
-            let mut dict: Dictionary<K, V> = Dictionary::with_capacity(key_vec.len());
+            // Sized so filling it stays under the 2/3 load threshold
+            let mut dict: Dictionary<K, V> = Dictionary::try_with_capacity(Dictionary::<K, V>::capacity_for(key_vec.len()))?;
             for _ in 0..key_vec.len() {
                 let key = key_vec.pop().unwrap();
                 let value = value_vec.pop().unwrap();
                 dict.insert(key, value);
             }
 
-            dict
+            Ok(dict)
         }
     }
     
     pub fn from_tuples(tuples: Vec<(K, V)>) -> Dictionary<K, V> {
-        if tuples.is_empty() {
-            panic!("Cannot create a zero-sized vec");
-        }
-        let mut dict: Dictionary<K, V> = Dictionary::with_capacity(tuples.len());
+        Dictionary::try_from_tuples(tuples).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_from_tuples(tuples: Vec<(K, V)>) -> Result<Dictionary<K, V>, DictError> {
+        let mut dict: Dictionary<K, V> = Dictionary::try_with_capacity(tuples.len())?;
 
         for (key, value) in tuples {
             dict.insert(key, value);
         }
 
-        dict
+        Ok(dict)
     }
 
-    pub fn size(&self) -> usize {
-        self.size
+    // Pairs up keys and values from the two iterators, stopping at the end
+    // of whichever runs out first. Unlike from_vecs() an empty dict is fine
+    pub fn from_zip<IK, IV>(keys: IK, values: IV) -> Dictionary<K, V>
+        where IK: IntoIterator<Item = K>,
+              IV: IntoIterator<Item = V> {
+        keys.into_iter().zip(values).collect()
     }
 
-    pub fn capacity(&self) -> usize {
-        self.capacity
-    }
+    // Groups the pairs by key, collecting every value given for a key into
+    // one vector in the order they came in
+    pub fn into_multimap_from_pairs<I: IntoIterator<Item = (K, V)>>(pairs: I) -> Dictionary<K, Vec<V>> {
+        let mut dict: Dictionary<K, Vec<V>> = Dictionary::new();
+
+        for (key, value) in pairs {
+            match dict.lookup(&key) {
+                Some(i) => {
+                    if let Bucket::Entry(d) = &mut dict.table[i] {
+                        d.1.push(value);
+                    }
+                },
+                None => dict.insert(key, vec![value])
+            }
+        }
 
-    pub fn get(&self, key: &K) -> Result<V, String> {
-       match self.lookup(key) {
-           Some(v) => Ok(v.1),
-           None => Err(format!("Key does not exist"))
-       }
+        dict
     }
+}
 
-    pub fn remove (&mut self, key: &K) -> Option<(K, V)>{
-        let output: Option<(K, V)>;
-        // If the key exists, remove it from the dictionary and add the key and value to the output
-        match self.lookup(key) {
-            Some(v) => {
-                self.table[v.2] = Bucket::Tombstone;
-                self.size -= 1;
-                output = Some((v.0, v.1));
-            },
-            None => {output = None;}
-        };
+#[allow(dead_code)]
+impl<K: Clone + Hash + PartialEq> Dictionary<K, usize> {
+    // Counts how many times each item comes up. The table is sized for every
+    // item being distinct, so it never resizes while counting
+    pub fn frequency_map<I: IntoIterator<Item = K>>(items: I) -> Dictionary<K, usize> {
+        let items = items.into_iter();
+        let mut dict: Dictionary<K, usize> = Dictionary::with_capacity(Dictionary::<K, usize>::capacity_for(items.size_hint().0));
 
-        if self.size < self.capacity/3 + 1 { // If current size is less than 2/3 half capacity, aka less than 1/3 capacity
-            self.resize(self.capacity/2); 
+        for item in items {
+            dict.entry(item).and_modify(|c| *c += 1).or_insert(1);
         }
 
-        output
+        dict
     }
+}
 
-    pub fn contains(&self, key: &K) -> bool {
-        self.lookup(key).is_some()
+#[allow(dead_code)]
+impl<K: Clone + Hash + PartialEq, V: Clone, S: BuildHasher + Clone> Dictionary<K, V, S> {
+    // Makes an empty dict that hashes keys with hashers built by hash_builder
+    pub fn with_hasher(hash_builder: S) -> Dictionary<K, V, S> {
+        Dictionary::with_capacity_and_hasher(8, hash_builder)
     }
 
-    fn get_hash(&self, key: &K) -> usize {
-        let mut s = DefaultHasher::new();
-        key.hash(&mut s);
-        s.finish() as usize
+    pub fn with_capacity_and_hasher(size: usize, hash_builder: S) -> Dictionary<K, V, S> {
+        Dictionary::try_with_capacity_and_hasher(size, hash_builder).unwrap_or_else(|e| panic!("{}", e))
     }
 
-    // Returns a vector of keys contained in the dict
-    pub fn keys(&self) -> Vec<&K> {
-        let mut key_vec: Vec<&K> = Vec::new();
-        for item in self.table.iter() {
-            if let Bucket::Entry(n) = item {
-                key_vec.push(&n.0);
-            }
+    // size is rounded up to the next power of two, so with_capacity(10)
+    // makes a 16 bucket table
+    pub fn try_with_capacity_and_hasher(size: usize, hash_builder: S) -> Result<Dictionary<K, V, S>, DictError> {
+        if size == 0 {
+            return Err(DictError::ZeroSizedDict);
         }
-        key_vec
+        let size = size.checked_next_power_of_two().ok_or(DictError::CapacityOverflow)?;
+        // A Vec can never take up more than isize::MAX bytes
+        match size.checked_mul(core::mem::size_of::<Bucket<K, V>>()) {
+            Some(bytes) if bytes <= isize::MAX as usize => (),
+            _ => return Err(DictError::CapacityOverflow)
+        }
+
+        Ok(Dictionary {
+            capacity: size,
+            size: 0,
+            table: vec![Bucket::Empty; size],
+            tombstone_limit: usize::MAX,
+            resize_policy: ResizePolicy::default(),
+            load_thresholds: None,
+            hash_builder,
+            generation: 0
+        })
     }
 
-    // Returns a vector of values contained in the dict
-    pub fn values(&self) -> Vec<&V> {
-        let mut value_vec: Vec<&V> = Vec::new();
-        for item in self.table.iter() {
-            if let Bucket::Entry(n) = item {
-                value_vec.push(&n.1);
-            }
-        }
-        value_vec
+    /* Performs a lookup using almost the exact same algorithm as insertion
+     * Returns Some(index) of the bucket holding the key if it exists, and None otherwise
+     */
+    fn lookup(&self, key: &K) -> Option<usize> { 
+        self.probe(key, self.get_hash(key)).ok()
     }
-    
-    // Returns a vector of (key, value) tuples containing every
-    // key value pairing in the dict
-    pub fn items(&self) -> Vec<(&K, &V)> {
-        let mut item_vec: Vec<(&K, &V)> = Vec::new();
-        for item in self.table.iter() {
-            if let Bucket::Entry(n) = item {
-                item_vec.push((&n.0, &n.1));
-            }
+
+    /* Walks the probe sequence for key, returning Ok(index) of the bucket holding
+     * the key if it exists, and Err(index) of the bucket a new entry for it
+     * should go in otherwise. That is the first tombstone passed on the way, or
+     * the empty bucket that ended the probing if there were no tombstones
+     * Each step to the next bucket is worked out by next_index()
+     */
+    fn probe(&self, key: &K, key_hash: usize) -> Result<usize, usize> {
+        let mut index = bucket_index(key_hash, self.capacity);
+        let mut perturb: usize = key_hash;
+        let mut first_tombstone: Option<usize> = None;
+        let mut distance: usize = 0;
+
+        loop {
+            match &self.table[index] {
+                Bucket::Entry(d) => {
+                    if d.0 == *key {
+                        break Ok(index);
+                    } else if self.ends_probe(d.2, index, distance) {
+                        break Err(index);
+                    } else {
+                        index = self.next_index(index, &mut perturb);
+                        distance += 1;
+                        continue;
+                    }
+                },
+
+                Bucket::Tombstone => {
+                    first_tombstone.get_or_insert(index);
+                    index = self.next_index(index, &mut perturb);
+                    distance += 1;
+                    continue;
+                }, 
+
+                Bucket::Empty => {
+                    break Err(first_tombstone.unwrap_or(index));
+                }
+            };
         }
-        item_vec
     }
-}
 
-impl<K, V> fmt::Display for Dictionary<K, V>
-    where K: fmt::Display + Clone + Hash,
-          V: fmt::Display + Clone {
+    #[cfg(not(feature = "robin-hood"))]
+    fn next_index(&self, index: usize, perturb: &mut usize) -> usize {
+        bucket_index(probe_step(index, perturb), self.capacity)
+    }
 
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut output_str = String::new();
-        output_str.push_str("{");
+    // Robin Hood needs to know how far an entry is from its first bucket
+    // from the index alone, so probing is linear and perturb goes unused
+    #[cfg(feature = "robin-hood")]
+    fn next_index(&self, index: usize, _perturb: &mut usize) -> usize {
+        bucket_index(index + 1, self.capacity)
+    }
 
-        for k in self.table.iter() // Iterate over all buckets containing an entry
-            .filter(|v| match v { Bucket::Entry(_n) => true, _ => false }) {
-            if let Bucket::Entry(d) = k {
-                write!(output_str, "{}: {}, ", d.0, d.1)?;
-            }
-        }
+    // How many buckets past its first one an entry with key_hash sitting in
+    // bucket index is
+    #[cfg(feature = "robin-hood")]
+    fn displacement(&self, key_hash: usize, index: usize) -> usize {
+        bucket_index(index + self.capacity - bucket_index(key_hash, self.capacity), self.capacity)
+    }
+
+    // Whether a probe that has come distance buckets can stop at the entry
+    // with key_hash in bucket index. Only Robin Hood tables can stop before
+    // an empty bucket
+    #[cfg(not(feature = "robin-hood"))]
+    #[inline]
+    fn ends_probe(&self, _key_hash: usize, _index: usize, _distance: usize) -> bool {
+        false
+    }
+
+    // A key that had come further than the entry sitting here would have
+    // taken its bucket when it was inserted, so it can't be further along
+    #[cfg(feature = "robin-hood")]
+    #[inline]
+    fn ends_probe(&self, key_hash: usize, index: usize, distance: usize) -> bool {
+        self.displacement(key_hash, index) < distance
+    }
 
-        let len = output_str.len();
-        if len > 1 {
-            output_str = String::from(&output_str[..len - 2]);
+    /* Places a new entry using Robin Hood insertion, the same as rebalance()
+     * but on every insert: an entry that is further from its first bucket
+     * than the one sitting in a bucket takes that bucket, and the one pushed
+     * out carries on from there until an empty bucket takes it
+     * Robin Hood tables never hold tombstones, see take_bucket(), so along
+     * every run of entries the displacements only drop where a new run
+     * starts, which is what lets probe() stop early
+     * Returns the index of the bucket the new entry went in
+     */
+    #[cfg(feature = "robin-hood")]
+    fn robin_hood_insert(&mut self, mut entry: (K, V, usize, usize)) -> usize {
+        let mut index = bucket_index(entry.2, self.capacity);
+        let mut placed: Option<usize> = None;
+
+        loop {
+            let evict = match &self.table[index] {
+                Bucket::Entry(d) => self.displacement(d.2, index) < self.displacement(entry.2, index),
+                _ => {
+                    entry.3 = index;
+                    self.table[index] = Bucket::Entry(entry);
+                    break *placed.get_or_insert(index);
+                }
+            };
+
+            if evict {
+                entry.3 = index;
+                let displaced = core::mem::replace(&mut self.table[index], Bucket::Entry(entry));
+                entry = match displaced {
+                    Bucket::Entry(d) => d,
+                    _ => unreachable!("Bucket was just checked to hold an entry")
+                };
+                placed.get_or_insert(index);
+            }
+            index = self.next_index(index, &mut 0);
+        }
+    }
+
+    // Inserts new items without regard for size of the dict, it is separated from 
+    // the insert() function to prevent recursion on resizing. 
+    // Returns the index of the bucket the item was placed in
+    fn force_insert(&mut self, key: K, value: V, key_hash: usize) -> usize {
+        match self.probe(&key, key_hash) {
+            Ok(index) => {
+                if let Bucket::Entry(d) = &mut self.table[index] {
+                    d.1 = value;
+                }
+                index
+            },
+
+            #[cfg(not(feature = "robin-hood"))]
+            Err(index) => {
+                self.table[index] = Bucket::Entry((key, value, key_hash, index));
+                index
+            }
+
+            #[cfg(feature = "robin-hood")]
+            Err(index) => self.robin_hood_insert((key, value, key_hash, index))
+        }
+    }
+
+    // Empties the table and makes a table twice the size, then reinserts all the entries
+    // new_capacity is rounded up to the next power of two
+    fn resize(&mut self, new_capacity: usize) {
+        self.rebuild(vec![Bucket::Empty; new_capacity.next_power_of_two()]);
+    }
+
+    // Swaps in table, which must be all empty buckets and a power of two
+    // long, and reinserts every entry into it
+    fn rebuild(&mut self, table: Vec<Bucket<K, V>>) {
+        debug_assert!(table.len().is_power_of_two());
+        self.generation += 1;
+        self.capacity = table.len();
+        let _table = core::mem::replace(&mut self.table, table);
+        for entry in _table {    
+            if let Bucket::Entry(d) = entry {
+                self.force_insert(d.0, d.1, d.2);
+            }
+        }
+    }
+
+    // Makes the dict compact itself whenever a removal leaves it with more
+    // than max_tombstones tombstones. 0 compacts after every removal and
+    // usize::MAX turns it off, which is the default
+    pub fn with_tombstone_limit(mut self, max_tombstones: usize) -> Self {
+        self.tombstone_limit = max_tombstones;
+        self
+    }
+
+    // Sets how the dict picks its new capacity when it grows or shrinks
+    pub fn with_resize_policy(mut self, policy: ResizePolicy) -> Self {
+        self.resize_policy = policy;
+        self
+    }
+
+    // Makes the dict grow and shrink by factor, which must be greater than 1
+    pub fn with_growth_factor(self, factor: f64) -> Self {
+        if factor.is_nan() || factor <= 1.0 {
+            panic!("Growth factor must be greater than 1");
+        }
+        self.with_resize_policy(ResizePolicy::Factor(factor))
+    }
+
+    /* Makes the table grow once size goes over high * capacity and shrink once
+     * it drops under low * capacity, in place of the usual 2/3 and 1/3
+     * high has to stay under 1 so probing always finds an empty bucket, and
+     * low has to be under high. Keeping low well under high / 2 stops the
+     * table from shrinking right back after it grows
+     */
+    pub fn with_load_factor_threshold(mut self, high: f64, low: f64) -> Self {
+        if !(0.0 < high && high < 1.0 && 0.0 <= low && low < high) {
+            panic!("Load thresholds must satisfy 0 <= low < high < 1");
+        }
+        self.load_thresholds = Some((high, low));
+        self.grow_to_hold(self.size);
+        self
+    }
+
+    // Checks whether size entries fit in capacity buckets without going over
+    // the high load threshold
+    fn holds(&self, capacity: usize, size: usize) -> bool {
+        match self.load_thresholds {
+            None => 2 * (capacity/3) >= size,
+            Some((high, _)) => size < capacity && size as f64 <= capacity as f64 * high
+        }
+    }
+
+    // Smallest power of two capacity that holds size entries under the high
+    // load threshold
+    fn capacity_to_hold(&self, size: usize) -> usize {
+        match self.load_thresholds {
+            None => Dictionary::<K, V>::capacity_for(size),
+            Some((high, _)) => {
+                let mut capacity = ((size as f64 / high) as usize).max(1);
+                while !self.holds(capacity, size) {
+                    capacity += 1;
+                }
+                capacity.next_power_of_two()
+            }
+        }
+    }
+
+    // Grows the table so that additional more entries fit without resizing.
+    // Does nothing if they already fit
+    pub fn reserve(&mut self, additional: usize) {
+        self.grow_to_hold(self.size + additional);
+    }
+
+    // Same as reserve(), but hands back an error instead of panicking or
+    // aborting if the capacity overflows or the table cannot be allocated
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocationError> {
+        let size = self.size.checked_add(additional).ok_or(AllocationError)?;
+        if self.holds(self.capacity, size) {
+            return Ok(());
+        }
+        if size > usize::MAX / 3 {
+            return Err(AllocationError);
+        }
+
+        let capacity = self.capacity_to_hold(size);
+        let mut table: Vec<Bucket<K, V>> = Vec::new();
+        table.try_reserve_exact(capacity).map_err(|_| AllocationError)?;
+        table.resize(capacity, Bucket::Empty);
+        self.rebuild(table);
+        Ok(())
+    }
+
+    // Shrinks the table to the smallest capacity that still holds every entry
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    // Shrinks the table as far as it can go without dropping below
+    // min_capacity. Does nothing if the table is already that small
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let new_capacity = min_capacity.max(self.capacity_to_hold(self.size)).next_power_of_two();
+        if new_capacity < self.capacity {
+            self.resize(new_capacity);
+        }
+    }
+
+    // Rebuilds the table at its current capacity, clearing out every tombstone
+    pub fn compact(&mut self) {
+        self.resize(self.capacity);
+    }
+
+    // Same as compact()
+    pub fn rehash(&mut self) {
+        self.compact();
+    }
+
+    /* Rebuilds the table at its current capacity using Robin Hood insertion:
+     * an entry that has probed further than the one sitting in a bucket takes
+     * that bucket, and the entry it pushed out carries on probing from where
+     * it was. Every entry still sits somewhere along its own probe sequence
+     * with no empty buckets before it, so lookups are unchanged, but probe
+     * lengths even out and the longest chains get shorter
+     *
+     * This is a one-off rebuild, meant to be called after a lot of inserts
+     * rather than on every one
+     */
+    pub fn rebalance(&mut self) {
+        self.generation += 1;
+        let old_table = core::mem::replace(&mut self.table, vec![Bucket::Empty; self.capacity]);
+        // Probe distance and perturb of the entry in each bucket
+        let mut probes: Vec<(usize, usize)> = vec![(0, 0); self.capacity];
+
+        for bucket in old_table {
+            if let Bucket::Entry(mut entry) = bucket {
+                let mut index = bucket_index(entry.2, self.capacity);
+                let mut perturb: usize = entry.2;
+                let mut distance: usize = 0;
+
+                loop {
+                    if !matches!(self.table[index], Bucket::Entry(_)) {
+                        entry.3 = index;
+                        self.table[index] = Bucket::Entry(entry);
+                        probes[index] = (distance, perturb);
+                        break;
+                    }
+
+                    if probes[index].0 < distance {
+                        entry.3 = index;
+                        let displaced = core::mem::replace(&mut self.table[index], Bucket::Entry(entry));
+                        let probe = core::mem::replace(&mut probes[index], (distance, perturb));
+                        entry = match displaced {
+                            Bucket::Entry(d) => d,
+                            _ => unreachable!("Bucket was just checked to hold an entry")
+                        };
+                        distance = probe.0;
+                        perturb = probe.1;
+                    }
+
+                    index = self.next_index(index, &mut perturb);
+                    distance += 1;
+                }
+            }
+        }
+    }
+
+    /* Clears out tombstones in place instead of rebuilding the table like
+     * compact() does. Returns how many tombstones were turned back into
+     * empty buckets
+     *
+     * First every entry is moved back into the earliest tombstone along its
+     * own probe sequence, if there is one before its bucket. Then any
+     * tombstone that no entry probes past on the way to its bucket can be
+     * emptied without breaking a lookup
+     */
+    pub fn reclaim_tombstones(&mut self) -> usize {
+        for index in 0..self.capacity {
+            let key_hash = match &self.table[index] {
+                Bucket::Entry(d) => d.2,
+                _ => continue
+            };
+            let mut probe = bucket_index(key_hash, self.capacity);
+            let mut perturb: usize = key_hash;
+
+            while probe != index {
+                if let Bucket::Tombstone = self.table[probe] {
+                    if let Bucket::Entry(mut d) = core::mem::replace(&mut self.table[index], Bucket::Tombstone) {
+                        d.3 = probe;
+                        self.table[probe] = Bucket::Entry(d);
+                    }
+                    break;
+                }
+                probe = self.next_index(probe, &mut perturb);
+            }
+        }
+
+        // Mark every bucket some entry probes past before reaching its own
+        let mut passed: Vec<bool> = vec![false; self.capacity];
+        for index in 0..self.capacity {
+            if let Bucket::Entry(d) = &self.table[index] {
+                let mut probe = bucket_index(d.2, self.capacity);
+                let mut perturb: usize = d.2;
+                while probe != index {
+                    passed[probe] = true;
+                    probe = self.next_index(probe, &mut perturb);
+                }
+            }
+        }
+
+        let mut reclaimed: usize = 0;
+        for (bucket, passed) in self.table.iter_mut().zip(passed) {
+            if matches!(bucket, Bucket::Tombstone) && !passed {
+                *bucket = Bucket::Empty;
+                reclaimed += 1;
+            }
+        }
+        self.generation += 1;
+        reclaimed
+    }
+
+    //Checks if a resize is needed before inserting the new item, resizes if needed
+    pub fn insert(&mut self, key: K, value: V) {
+        self.insert_index(key, value);
+    }
+
+    // Does the work of insert(), returning the index of the bucket the item ended up in
+    fn insert_index(&mut self, key: K, value: V) -> usize {
+        let hash = self.get_hash(&key);
+        if self.probe(&key, hash).is_ok() { // Overwriting takes no new bucket
+            return self.force_insert(key, value, hash);
+        }
+
+        self.size += 1;
+        self.generation += 1;
+        self.grow_if_full();
+        self.force_insert(key, value, hash)
+    }
+
+    // Inserts key only if it is not already in the dict, handing value back
+    // otherwise. Only probes once unless the table has to grow
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<(), V> {
+        let hash = self.get_hash(&key);
+        match self.probe(&key, hash) {
+            Ok(_) => Err(value),
+            Err(index) => {
+                self.size += 1;
+                self.generation += 1;
+                // Robin Hood may need to move other entries, so it can't just
+                // drop the entry into the bucket probe() found
+                if self.grow_if_full() || cfg!(feature = "robin-hood") {
+                    self.force_insert(key, value, hash);
+                } else {
+                    self.table[index] = Bucket::Entry((key, value, hash, index));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // Grows the table following the resize policy if size has gone past the
+    // high load threshold. Returns whether it grew
+    fn grow_if_full(&mut self) -> bool {
+        if self.holds(self.capacity, self.size) {
+            return false;
+        }
+
+        let mut new_capacity = self.capacity;
+        while !self.holds(new_capacity, self.size) {
+            new_capacity = self.resize_policy.grow(new_capacity).max(new_capacity + 1);
+        }
+        self.resize(new_capacity);
+        true
+    }
+
+    
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // Returns a number that changes whenever an entry is added or removed or
+    // the table is rebuilt
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    // Same as generation(), meant to be handed back to is_modified_since()
+    pub fn snapshot_generation(&self) -> u64 {
+        self.generation
+    }
+
+    // Checks whether the dict has changed since generation was taken
+    pub fn is_modified_since(&self, generation: u64) -> bool {
+        self.generation != generation
+    }
+
+    // Captures the current entries so they can be put back with restore()
+    pub fn snapshot(&self) -> DictSnapshot<K, V> {
+        let entries: Vec<(K, V)> = self.iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        DictSnapshot { entries: Arc::new(entries) }
+    }
+
+    // Throws away every entry and puts back the ones in snapshot
+    pub fn restore(&mut self, snapshot: DictSnapshot<K, V>) {
+        self.drain();
+        self.reserve(snapshot.entries.len());
+        for (key, value) in snapshot.entries.iter() {
+            self.insert(key.clone(), value.clone());
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Counts the buckets left behind by removed entries
+    pub fn tombstone_count(&self) -> usize {
+        self.table.iter()
+            .filter(|b| matches!(b, Bucket::Tombstone))
+            .count()
+    }
+
+    // Fraction of the buckets that hold an entry
+    pub fn load_factor(&self) -> f64 {
+        self.size as f64 / self.capacity as f64
+    }
+
+    // Fraction of the buckets that are tombstones. Counting them is O(n)
+    pub fn tombstone_ratio(&self) -> f64 {
+        self.tombstone_count() as f64 / self.capacity as f64
+    }
+
+    pub fn stats(&self) -> DictStats {
+        let tombstone_count = self.tombstone_count();
+
+        DictStats {
+            size: self.size,
+            capacity: self.capacity,
+            load_factor: self.load_factor(),
+            tombstone_count,
+            tombstone_ratio: tombstone_count as f64 / self.capacity as f64
+        }
+    }
+
+    // Returns a summary of the table's capacity, size, and tombstones
+    pub fn load_stats(&self) -> LoadStats {
+        let tombstones = self.tombstone_count();
+        let used = self.size + tombstones;
+
+        LoadStats {
+            capacity: self.capacity,
+            size: self.size,
+            tombstones,
+            live_load: self.size as f64 / self.capacity as f64,
+            effective_load: used as f64 / self.capacity as f64,
+            fragmentation_ratio: if used == 0 { 0.0 } else { tombstones as f64 / used as f64 }
+        }
+    }
+
+    // Approximate number of bytes the dict takes up: the struct itself plus
+    // every bucket in the table. Memory that keys or values own on the heap,
+    // like the contents of a String, is not counted
+    pub fn estimate_memory_bytes(&self) -> usize {
+        core::mem::size_of::<Bucket<K, V>>() * self.capacity + core::mem::size_of::<Dictionary<K, V, S>>()
+    }
+
+    // Returns every bucket in the table on its own line, including empty ones
+    // and tombstones, for tracking down probing and resizing problems
+    pub fn debug_table(&self) -> String
+        where K: fmt::Debug, V: fmt::Debug {
+        let mut output_str = String::new();
+        for (index, item) in self.table.iter().enumerate() {
+            match item {
+                Bucket::Entry(d) => writeln!(output_str, "{}: {:?}: {:?} (hash {}, index {})", index, d.0, d.1, d.2, d.3),
+                Bucket::Empty => writeln!(output_str, "{}: Empty", index),
+                Bucket::Tombstone => writeln!(output_str, "{}: Tombstone", index)
+            }.expect("Writing to a String cannot fail");
+        }
+        output_str
+    }
+
+    // Writes the dict out as "key:value,key:value", the format parse()
+    // reads back in. Pairs come out in bucket order
+    pub fn to_compact_string(&self) -> String
+        where K: fmt::Display, V: fmt::Display {
+        self.map_collect::<Vec<String>, _, _>(|k, v| format!("{}:{}", k, v)).join(",")
+    }
+
+    // Checks every invariant the dict relies on and returns all the ones that
+    // are broken, rather than stopping at the first. Empty means all is well
+    pub fn verify_integrity(&self) -> Vec<IntegrityError>
+        where K: fmt::Debug {
+        let mut errors: Vec<IntegrityError> = Vec::new();
+
+        if self.capacity != self.table.len() {
+            errors.push(IntegrityError::CapacityMismatch { stored: self.capacity, table_len: self.table.len() });
+        }
+
+        let mut actual: usize = 0;
+        for (index, item) in self.table.iter().enumerate() {
+            if let Bucket::Entry(n) = item {
+                actual += 1;
+                let computed_hash = self.get_hash(&n.0);
+                if n.2 != computed_hash {
+                    errors.push(IntegrityError::HashMismatch {
+                        key_repr: format!("{:?}", n.0),
+                        stored_hash: n.2,
+                        computed_hash
+                    });
+                }
+                if n.3 != index {
+                    errors.push(IntegrityError::IndexMismatch { stored_index: n.3, actual_index: index });
+                }
+            }
+        }
+
+        if self.size != actual {
+            errors.push(IntegrityError::SizeCountMismatch { stored: self.size, actual });
+        }
+
+        errors
+    }
+
+    // O(1) approximation of the number of entries, guaranteed never to be
+    // lower than the real count. It is currently exact since size is kept
+    // up to date on every insert and remove
+    pub fn len_estimate(&self) -> usize {
+        // TODO: use bitmap popcount when available
+        self.size
+    }
+
+    // Returns a reference to the value for key, or None if the key is missing
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.lookup(key).and_then(|i| self.entry_at(i)).map(|e| e.1)
+    }
+
+    // Same as get(), but clones the value out
+    pub fn get_cloned(&self, key: &K) -> Option<V> {
+        self.get(key).cloned()
+    }
+
+    // Same as get(), but a missing key is a DictError::KeyNotFound
+    pub fn try_get(&self, key: &K) -> Result<&V, DictError> {
+        self.get(key).ok_or(DictError::KeyNotFound)
+    }
+
+    // Calls f on the value for key if there is one, returning whether there was
+    pub fn update_with<F: FnOnce(&mut V)>(&mut self, key: &K, f: F) -> bool {
+        match self.get_mut(key) {
+            Some(v) => {
+                f(v);
+                true
+            },
+            None => false
+        }
+    }
+
+    // Calls update on the value for key if there is one, otherwise inserts
+    // what default returns. update is not called on the inserted value
+    pub fn update_or_insert_with<F, G>(&mut self, key: K, update: F, default: G)
+        where F: FnOnce(&mut V),
+              G: FnOnce() -> V {
+        match self.entry(key) {
+            Entry::Occupied(mut e) => update(e.get_mut()),
+            Entry::Vacant(e) => {
+                e.insert(default());
+            }
+        }
+    }
+
+    // Returns references to the stored key and its value without cloning either
+    pub fn get_key_value(&self, key: &K) -> Option<(&K, &V)> {
+        self.lookup(key).and_then(|i| self.entry_at(i))
+    }
+
+    // Looks key up once and returns an Entry for reading, changing, or
+    // inserting its value without looking it up again
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        match self.lookup(&key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { dict: self, index }),
+            None => Entry::Vacant(VacantEntry { dict: self, key })
+        }
+    }
+
+    // Returns a mutable reference to the value for key, so it can be changed
+    // without removing and reinserting it
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.lookup(key)?;
+        match &mut self.table[index] {
+            Bucket::Entry(d) => Some(&mut d.1),
+            _ => None
+        }
+    }
+
+    // Looks up every key in keys and returns the results in the same order,
+    // None for keys that are not in the dict
+    pub fn bulk_get<'a>(&'a self, keys: &[&K]) -> Vec<Option<&'a V>> {
+        keys.iter()
+            .map(|k| self.lookup(k).and_then(|i| self.entry_at(i)).map(|e| e.1))
+            .collect()
+    }
+
+    // Returns the value for key, computing and inserting it first if the key is
+    // missing. compute is only called on a miss, and if it fails nothing is
+    // inserted and its error is handed back
+    pub fn get_cached<E, F: FnOnce(&K) -> Result<V, E>>(&mut self, key: K, compute: F) -> Result<&V, E> {
+        let index = match self.lookup(&key) {
+            Some(i) => i,
+            None => {
+                let value = compute(&key)?;
+                self.insert_index(key, value)
+            }
+        };
+
+        match self.entry_at(index) {
+            Some(v) => Ok(v.1),
+            None => unreachable!("Bucket at a found index should hold an entry")
+        }
+    }
+
+    // Removes key from the dict, returning its value
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove_entry(key).map(|d| d.1)
+    }
+
+    // Same as remove()
+    pub fn take(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+
+    // Empties bucket index, which must hold an entry, and returns the entry
+    // that was in it. It is left as a tombstone so later probes carry on past
+    #[cfg(not(feature = "robin-hood"))]
+    fn take_bucket(&mut self, index: usize) -> (K, V, usize, usize) {
+        match core::mem::replace(&mut self.table[index], Bucket::Tombstone) {
+            Bucket::Entry(d) => d,
+            _ => unreachable!("Bucket being taken should hold an entry")
+        }
+    }
+
+    /* Robin Hood version of take_bucket(), using backward shift deletion
+     * instead of a tombstone: every entry after index that isn't in its first
+     * bucket moves back one, up to the first empty bucket or entry that is
+     * already home. That keeps the table free of tombstones
+     */
+    #[cfg(feature = "robin-hood")]
+    fn take_bucket(&mut self, index: usize) -> (K, V, usize, usize) {
+        let output = match core::mem::replace(&mut self.table[index], Bucket::Empty) {
+            Bucket::Entry(d) => d,
+            _ => unreachable!("Bucket being taken should hold an entry")
+        };
+
+        let mut hole = index;
+        loop {
+            let next = self.next_index(hole, &mut 0);
+            let shift = match &self.table[next] {
+                Bucket::Entry(d) => self.displacement(d.2, next) > 0,
+                _ => false
+            };
+            if !shift {
+                break;
+            }
+            if let Bucket::Entry(mut d) = core::mem::replace(&mut self.table[next], Bucket::Empty) {
+                d.3 = hole;
+                self.table[hole] = Bucket::Entry(d);
+            }
+            hole = next;
+        }
+
+        output
+    }
+
+    // Removes and returns the first entry in bucket order, or None if the dict
+    // is empty. Finding it is O(capacity) when the front of the table is sparse
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        let index = self.table.iter().position(|b| matches!(b, Bucket::Entry(_)))?;
+        self.size -= 1;
+        self.generation += 1;
+        let d = self.take_bucket(index);
+        let output = Some((d.0, d.1));
+
+        self.tidy_after_remove();
+
+        output
+    }
+
+    // Removes key from the dict, returning the stored key along with its value
+    pub fn remove_entry(&mut self, key: &K) -> Option<(K, V)> {
+        // If the key exists, remove it from the dictionary and add the key and value to the output
+        let output: Option<(K, V)> = match self.lookup(key) {
+            Some(i) => {
+                self.size -= 1;
+                self.generation += 1;
+                let d = self.take_bucket(i);
+                Some((d.0, d.1))
+            },
+            None => None
+        };
+
+        self.tidy_after_remove();
+
+        output
+    }
+
+    // Moves the value stored under old over to new, returning whether it did.
+    // Nothing changes if old is missing or new is already in the dict
+    pub fn rename_key(&mut self, old: &K, new: K) -> bool {
+        let index = match self.lookup(old) {
+            Some(i) if !self.contains(&new) => i,
+            _ => return false
+        };
+
+        // The entry count stays the same, so this skips insert()'s size
+        // bookkeeping and resize check
+        self.generation += 1;
+        let d = self.take_bucket(index);
+        let hash = self.get_hash(&new);
+        self.force_insert(new, d.1, hash);
+        self.tidy_after_remove();
+
+        true
+    }
+
+    // Exchanges the values of two keys in place, returning false and leaving
+    // the dict as it is unless both keys are in it
+    pub fn swap_values(&mut self, key1: &K, key2: &K) -> bool {
+        let (i, j) = match (self.lookup(key1), self.lookup(key2)) {
+            (Some(i), Some(j)) => (i.min(j), i.max(j)),
+            _ => return false
+        };
+        if i == j {
+            return true;
+        }
+
+        let (low, high) = self.table.split_at_mut(j);
+        if let (Bucket::Entry(a), Bucket::Entry(b)) = (&mut low[i], &mut high[0]) {
+            core::mem::swap(&mut a.1, &mut b.1);
+        }
+        true
+    }
+
+    // Keeps only the entries f returns true for. f gets a mutable reference to
+    // each value, so it can also change the ones it keeps
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        let size = self.size;
+        for item in self.table.iter_mut() {
+            if let Bucket::Entry(n) = item {
+                if !f(&n.0, &mut n.1) {
+                    *item = Bucket::Tombstone;
+                    self.size -= 1;
+                }
+            }
+        }
+
+        if self.size < size {
+            self.generation += 1;
+            // Robin Hood probes can't run over the tombstones left behind
+            if cfg!(feature = "robin-hood") {
+                self.compact();
+            }
+            self.tidy_after_remove();
+        }
+    }
+
+    // Returns a new dict of the entries f returns true for, leaving self as
+    // it is. Sized for every entry to match, so it never resizes
+    pub fn filter<F: FnMut(&K, &V) -> bool>(&self, mut f: F) -> Dictionary<K, V, S> {
+        self.filter_map(|k, v| if f(k, v) { Some(v.clone()) } else { None })
+    }
+
+    // Like filter(), but f also maps the values it keeps, returning None for
+    // the entries to leave out
+    pub fn filter_map<W: Clone, F: FnMut(&K, &V) -> Option<W>>(&self, mut f: F) -> Dictionary<K, W, S> {
+        let capacity = Dictionary::<K, V>::capacity_for(self.size);
+        let mut dict: Dictionary<K, W, S> = Dictionary::with_capacity_and_hasher(capacity, self.hash_builder.clone());
+        for (key, value) in self.iter() {
+            if let Some(w) = f(key, value) {
+                dict.insert(key.clone(), w);
+            }
+        }
+        dict
+    }
+
+    // Shrinks the table if it has gotten sparse, then compacts it if there
+    // are more tombstones than the limit allows
+    fn tidy_after_remove(&mut self) {
+        let sparse = match self.load_thresholds {
+            None => self.size < self.capacity/3 + 1, // If current size is less than 2/3 half capacity, aka less than 1/3 capacity
+            Some((_, low)) => (self.size as f64) < self.capacity as f64 * low
+        };
+        if sparse {
+            // Never below what insert() would grow back to for this size
+            let new_capacity = round_down_to_power_of_two(self.resize_policy.shrink(self.capacity))
+                .max(self.capacity_to_hold(self.size));
+            if new_capacity < self.capacity {
+                self.resize(new_capacity);
+            }
+        }
+
+        if self.tombstone_limit != usize::MAX && self.tombstone_count() > self.tombstone_limit {
+            self.compact();
+        }
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.lookup(key).is_some()
+    }
+
+    // Checks whether any key maps to value. Unlike contains() this has to
+    // scan the whole table, so it is O(n)
+    pub fn contains_value(&self, value: &V) -> bool
+        where V: PartialEq {
+        self.iter().any(|(_, v)| v == value)
+    }
+
+    // Returns a vector of every key that maps to value. This scans the whole
+    // table, so it is O(n)
+    pub fn keys_with_value(&self, value: &V) -> Vec<&K>
+        where V: PartialEq {
+        self.iter()
+            .filter(|(_, v)| *v == value)
+            .map(|(k, _)| k)
+            .collect()
+    }
+
+    // Hints the CPU to pull the first bucket key would be looked up in into
+    // cache, so a later lookup of key waits less on memory. Only does
+    // anything on x86_64
+    #[cfg(feature = "prefetch")]
+    pub fn prefetch(&self, key: &K) {
+        let index = bucket_index(self.get_hash(key), self.capacity);
+        let bucket = &self.table[index] as *const Bucket<K, V>;
+
+        #[cfg(target_arch = "x86_64")]
+        unsafe {
+            use core::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+            _mm_prefetch::<_MM_HINT_T0>(bucket as *const i8);
+        }
+
+        #[cfg(not(target_arch = "x86_64"))]
+        let _ = bucket;
+    }
+
+    fn get_hash(&self, key: &K) -> usize {
+        self.hash_builder.hash_one(key) as usize
+    }
+
+    // Returns a vector of keys contained in the dict
+    pub fn keys(&self) -> Vec<&K> {
+        let mut key_vec: Vec<&K> = Vec::new();
+        for item in self.table.iter() {
+            if let Bucket::Entry(n) = item {
+                key_vec.push(&n.0);
+            }
+        }
+        key_vec
+    }
+
+    // Returns a vector of values contained in the dict
+    pub fn values(&self) -> Vec<&V> {
+        let mut value_vec: Vec<&V> = Vec::new();
+        for item in self.table.iter() {
+            if let Bucket::Entry(n) = item {
+                value_vec.push(&n.1);
+            }
+        }
+        value_vec
+    }
+    
+    // Returns a vector of (key, value) tuples containing every
+    // key value pairing in the dict
+    pub fn items(&self) -> Vec<(&K, &V)> {
+        let mut item_vec: Vec<(&K, &V)> = Vec::new();
+        for item in self.table.iter() {
+            if let Bucket::Entry(n) = item {
+                item_vec.push((&n.0, &n.1));
+            }
+        }
+        item_vec
+    }
+
+    // Returns a vector of (key, hash) tuples with the hash stored alongside
+    // each key, for checking how well the keys' hashes are spread out
+    pub fn key_hashes(&self) -> Vec<(&K, usize)> {
+        let mut hash_vec: Vec<(&K, usize)> = Vec::new();
+        for item in self.table.iter() {
+            if let Bucket::Entry(n) = item {
+                hash_vec.push((&n.0, n.2));
+            }
+        }
+        hash_vec
+    }
+
+    // Consumes the dict and wraps every value in a single element vector
+    pub fn into_multimap(self) -> Dictionary<K, Vec<V>, S> {
+        let mut dict: Dictionary<K, Vec<V>, S> = Dictionary::with_capacity_and_hasher(self.capacity, self.hash_builder.clone());
+        for bucket in self.table {
+            if let Bucket::Entry(d) = bucket {
+                dict.insert(d.0, vec![d.1]);
+            }
+        }
+        dict
+    }
+
+    // Returns a vector of (key, values) tuples where every key has a single
+    // value, for handing the dict to code that expects several values per key
+    pub fn to_vec_map(&self) -> Vec<(K, Vec<V>)> {
+        let mut item_vec: Vec<(K, Vec<V>)> = Vec::new();
+        for item in self.table.iter() {
+            if let Bucket::Entry(n) = item {
+                item_vec.push((n.0.clone(), vec![n.1.clone()]));
+            }
+        }
+        item_vec
+    }
+
+    // Calls f on every entry and collects what it returns into any collection,
+    // without building a vector of items first
+    pub fn map_collect<B, T, F>(&self, mut f: F) -> B
+        where B: FromIterator<T>,
+              F: FnMut(&K, &V) -> T {
+        self.table.iter()
+            .filter_map(|b| match b {
+                Bucket::Entry(d) => Some(f(&d.0, &d.1)),
+                _ => None
+            })
+            .collect()
+    }
+
+    // Calls f on every entry
+    pub fn for_each<F: FnMut(&K, &V)>(&self, mut f: F) {
+        for (key, value) in self.iter() {
+            f(key, value);
+        }
+    }
+
+    // Threads an accumulator through f for every entry, starting from init
+    pub fn fold<B, F: FnMut(B, &K, &V) -> B>(&self, init: B, mut f: F) -> B {
+        self.iter().fold(init, |acc, (k, v)| f(acc, k, v))
+    }
+
+    // Checks whether f returns true for any entry, stopping at the first one
+    pub fn any<F: FnMut(&K, &V) -> bool>(&self, mut f: F) -> bool {
+        self.iter().any(|(k, v)| f(k, v))
+    }
+
+    // Checks whether f returns true for every entry, stopping at the first
+    // one it doesn't. An empty dict gives true
+    pub fn all<F: FnMut(&K, &V) -> bool>(&self, mut f: F) -> bool {
+        self.iter().all(|(k, v)| f(k, v))
+    }
+
+    // Counts the entries f returns true for, without allocating anything
+    pub fn count_where<F: FnMut(&K, &V) -> bool>(&self, mut f: F) -> usize {
+        self.iter().filter(|(k, v)| f(k, v)).count()
+    }
+
+    // Returns the first entry in bucket order that f returns true for
+    pub fn find<F: FnMut(&K, &V) -> bool>(&self, mut f: F) -> Option<(&K, &V)> {
+        self.iter().find(|(k, v)| f(k, v))
+    }
+
+    // Returns a vector of (key, value) tuples sorted by what f returns for
+    // each key, for keys that have no ordering of their own
+    pub fn items_sorted_by_key<B: Ord, F: FnMut(&K) -> B>(&self, mut f: F) -> Vec<(&K, &V)> {
+        let mut item_vec = self.items();
+        item_vec.sort_by_key(|item| f(item.0));
+        item_vec
+    }
+
+    // Returns a vector of (key, value) tuples sorted with compare
+    pub fn to_sorted_vec_by<F>(&self, compare: F) -> Vec<(&K, &V)>
+        where F: FnMut(&(&K, &V), &(&K, &V)) -> Ordering {
+        let mut item_vec = self.items();
+        item_vec.sort_by(compare);
+        item_vec
+    }
+
+    // Calls f on every value in the dict, letting it modify the value in place
+    pub fn map_values_mut<F: FnMut(&mut V)>(&mut self, mut f: F) {
+        for item in self.table.iter_mut() {
+            if let Bucket::Entry(n) = item {
+                f(&mut n.1);
+            }
+        }
+    }
+
+    // Applies f to every value in place, same as map_values_mut()
+    pub fn transform<F: FnMut(&mut V)>(&mut self, f: F) {
+        self.map_values_mut(f);
+    }
+
+    /* Consumes the dict and rebuilds it with f applied to every key, for
+     * canonicalizing keys (lowercasing, trimming, and so on)
+     *
+     * If two keys normalize to the same key only one entry survives, and it
+     * is the one that came last in bucket order. Bucket order depends on the
+     * hashes, so which value wins should not be relied on
+     */
+    pub fn normalize<F: Fn(K) -> K>(self, f: F) -> Dictionary<K, V, S> {
+        let mut dict: Dictionary<K, V, S> = Dictionary::with_capacity_and_hasher(self.capacity, self.hash_builder.clone());
+
+        for bucket in self.table {
+            if let Bucket::Entry(d) = bucket {
+                dict.insert(f(d.0), d.1);
+            }
+        }
+
+        dict
+    }
+
+    // Inserts a clone of every entry in other, overwriting values for keys
+    // that are already in self
+    pub fn extend_from_dict(&mut self, other: &Dictionary<K, V, S>) {
+        for item in other.table.iter() {
+            if let Bucket::Entry(n) = item {
+                self.insert(n.0.clone(), n.1.clone());
+            }
+        }
+    }
+
+    // Returns a dict with only the entries whose keys are in keys. Keys that
+    // are not in self are skipped
+    pub fn sub_dict<'a, I: IntoIterator<Item = &'a K>>(&self, keys: I) -> Dictionary<K, V, S>
+        where K: 'a {
+        let found: Vec<(&K, &V)> = keys.into_iter()
+            .filter_map(|k| self.get_key_value(k))
+            .collect();
+
+        let capacity = Dictionary::<K, V>::capacity_for(found.len());
+        let mut dict: Dictionary<K, V, S> = Dictionary::with_capacity_and_hasher(capacity, self.hash_builder.clone());
+        for (key, value) in found {
+            dict.insert(key.clone(), value.clone());
+        }
+        dict
+    }
+
+    // Returns a dict with every entry except the ones whose keys are in keys
+    pub fn except_keys<'a, I: IntoIterator<Item = &'a K>>(&self, keys: I) -> Dictionary<K, V, S>
+        where K: 'a {
+        let excluded: Dictionary<&K, ()> = keys.into_iter().map(|k| (k, ())).collect();
+        let kept: Vec<(&K, &V)> = self.iter()
+            .filter(|(k, _)| !excluded.contains(k))
+            .collect();
+
+        let capacity = Dictionary::<K, V>::capacity_for(kept.len());
+        let mut dict: Dictionary<K, V, S> = Dictionary::with_capacity_and_hasher(capacity, self.hash_builder.clone());
+        for (key, value) in kept {
+            dict.insert(key.clone(), value.clone());
+        }
+        dict
+    }
+
+    // Consumes the dict and splits it in two, entries f returns true for in
+    // the first dict and the rest in the second. Both are sized to fit
+    pub fn partition<F: FnMut(&K, &V) -> bool>(self, mut f: F) -> (Dictionary<K, V, S>, Dictionary<K, V, S>) {
+        let mut matched: Vec<(K, V, usize)> = Vec::new();
+        let mut rest: Vec<(K, V, usize)> = Vec::new();
+        for bucket in self.table {
+            if let Bucket::Entry(d) = bucket {
+                if f(&d.0, &d.1) {
+                    matched.push((d.0, d.1, d.2));
+                } else {
+                    rest.push((d.0, d.1, d.2));
+                }
+            }
+        }
+
+        (Dictionary::from_hashed(matched, self.hash_builder.clone()), Dictionary::from_hashed(rest, self.hash_builder.clone()))
+    }
+
+    // Same as partition() but clones the entries and leaves self alone
+    pub fn partition_ref<F: FnMut(&K, &V) -> bool>(&self, mut f: F) -> (Dictionary<K, V, S>, Dictionary<K, V, S>) {
+        let mut matched: Vec<(K, V, usize)> = Vec::new();
+        let mut rest: Vec<(K, V, usize)> = Vec::new();
+        for item in self.table.iter() {
+            if let Bucket::Entry(n) = item {
+                if f(&n.0, &n.1) {
+                    matched.push((n.0.clone(), n.1.clone(), n.2));
+                } else {
+                    rest.push((n.0.clone(), n.1.clone(), n.2));
+                }
+            }
+        }
+
+        (Dictionary::from_hashed(matched, self.hash_builder.clone()), Dictionary::from_hashed(rest, self.hash_builder.clone()))
+    }
+
+    // Builds a dict sized to fit entries, which must have distinct keys and
+    // come with their hashes already worked out by hash_builder
+    fn from_hashed(entries: Vec<(K, V, usize)>, hash_builder: S) -> Dictionary<K, V, S> {
+        let capacity = Dictionary::<K, V>::capacity_for(entries.len());
+        let mut dict: Dictionary<K, V, S> = Dictionary::with_capacity_and_hasher(capacity, hash_builder);
+        dict.size = entries.len();
+        for (key, value, key_hash) in entries {
+            dict.force_insert(key, value, key_hash);
+        }
+        dict
+    }
+
+    // Moves every entry of other into self, overwriting values for keys that
+    // are already in self. The table is grown once up front to fit both dicts
+    pub fn merge(&mut self, other: Dictionary<K, V, S>) {
+        self.grow_to_hold(self.size + other.size);
+        for bucket in other.table {
+            if let Bucket::Entry(d) = bucket {
+                self.insert(d.0, d.1);
+            }
+        }
+    }
+
+    // Same as merge() but clones the entries of other instead of taking them
+    pub fn merge_from(&mut self, other: &Dictionary<K, V, S>) {
+        self.grow_to_hold(self.size + other.size);
+        self.extend_from_dict(other);
+    }
+
+    // Like merge_from(), but a key already in self gets f(key, value in self,
+    // value in other) instead of being overwritten. Keys only in self are left
+    // alone and keys only in other are cloned in
+    pub fn merge_with<F: FnMut(&K, &V, &V) -> V>(&mut self, other: &Dictionary<K, V, S>, mut f: F) {
+        self.grow_to_hold(self.size + other.size);
+        for (key, value) in other.iter() {
+            match self.get_mut(key) {
+                Some(existing) => *existing = f(key, existing, value),
+                None => self.insert(key.clone(), value.clone())
+            }
+        }
+    }
+
+    // Resizes once to a capacity that fits size entries, if the table is too small
+    fn grow_to_hold(&mut self, size: usize) {
+        if !self.holds(self.capacity, size) {
+            self.resize(self.capacity_to_hold(size));
+        }
+    }
+
+    // Returns a dict with every key from self and other, each value tagged
+    // with which of the two dicts the key was found in
+    pub fn symmetric_merge<W: Clone>(&self, other: &Dictionary<K, W, S>) -> Dictionary<K, MergeSource<V, W>, S> {
+        let mut dict: Dictionary<K, MergeSource<V, W>, S> = Dictionary::with_hasher(self.hash_builder.clone());
+
+        for item in self.table.iter() {
+            if let Bucket::Entry(n) = item {
+                let value = match other.lookup(&n.0).and_then(|i| other.entry_at(i)) {
+                    Some(e) => MergeSource::Both(n.1.clone(), e.1.clone()),
+                    None => MergeSource::Left(n.1.clone())
+                };
+                dict.insert(n.0.clone(), value);
+            }
+        }
+
+        for item in other.table.iter() {
+            if let Bucket::Entry(n) = item {
+                if !self.contains(&n.0) {
+                    dict.insert(n.0.clone(), MergeSource::Right(n.1.clone()));
+                }
+            }
+        }
+
+        dict
+    }
+
+    // Consumes the dict and splits it into n dicts, putting each entry in
+    // shard hash(key) % n. Shards are sized up front and entries keep their
+    // stored hashes, so nothing gets rehashed or resized along the way
+    pub fn auto_shard(self, n: usize) -> Vec<Dictionary<K, V, S>> {
+        if n == 0 {
+            panic!("Cannot split a dict into zero shards");
+        }
+
+        let mut counts: Vec<usize> = vec![0; n];
+        for item in self.table.iter() {
+            if let Bucket::Entry(d) = item {
+                counts[d.2 % n] += 1;
+            }
+        }
+
+        let mut shards: Vec<Dictionary<K, V, S>> = counts.iter()
+            .map(|c| Dictionary::with_capacity_and_hasher(Dictionary::<K, V>::capacity_for(*c), self.hash_builder.clone()))
+            .collect();
+        for bucket in self.table {
+            if let Bucket::Entry(d) = bucket {
+                let shard = &mut shards[d.2 % n];
+                shard.size += 1;
+                shard.force_insert(d.0, d.1, d.2);
+            }
+        }
+
+        shards
+    }
+
+    // Removes entries whose value already showed up earlier in bucket order,
+    // so that no two keys are left sharing a value
+    pub fn dedup(&mut self)
+        where V: Hash + Eq {
+        let mut seen: Dictionary<&V, ()> = Dictionary::new();
+        let mut duplicates: Vec<K> = Vec::new();
+        for item in self.table.iter() {
+            if let Bucket::Entry(n) = item {
+                if seen.try_insert(&n.1, ()).is_err() {
+                    duplicates.push(n.0.clone());
+                }
+            }
+        }
+
+        for key in duplicates.iter() {
+            self.remove(key);
+        }
+    }
+
+    // Same as map_values_mut()
+    pub fn map_values_in_place<F: FnMut(&mut V)>(&mut self, f: F) {
+        self.map_values_mut(f);
+    }
+
+    // Returns a new dict with the same keys and f applied to every value.
+    // The table is copied bucket for bucket, so nothing gets rehashed
+    pub fn map_values<W: Clone, F: FnMut(&V) -> W>(&self, mut f: F) -> Dictionary<K, W, S> {
+        let table: Vec<Bucket<K, W>> = self.table.iter()
+            .map(|b| match b {
+                Bucket::Entry(d) => Bucket::Entry((d.0.clone(), f(&d.1), d.2, d.3)),
+                Bucket::Empty => Bucket::Empty,
+                Bucket::Tombstone => Bucket::Tombstone
+            })
+            .collect();
+
+        Dictionary {
+            capacity: self.capacity,
+            size: self.size,
+            table,
+            tombstone_limit: self.tombstone_limit,
+            resize_policy: self.resize_policy,
+            load_thresholds: self.load_thresholds,
+            hash_builder: self.hash_builder.clone(),
+            generation: 0
+        }
+    }
+
+    // Returns a new dict with f applied to every key and the values cloned.
+    // If two keys map to the same new key the one later in bucket order wins
+    pub fn map_keys<J: Clone + Hash + PartialEq, F: FnMut(&K) -> J>(&self, mut f: F) -> Dictionary<J, V, S> {
+        // Never holds more entries than self, so it never needs to resize
+        let mut dict: Dictionary<J, V, S> = Dictionary::with_capacity_and_hasher(self.capacity, self.hash_builder.clone());
+        dict.tombstone_limit = self.tombstone_limit;
+        dict.resize_policy = self.resize_policy;
+        dict.load_thresholds = self.load_thresholds;
+
+        for item in self.table.iter() {
+            if let Bucket::Entry(n) = item {
+                let key = f(&n.0);
+                let hash = dict.get_hash(&key);
+                if dict.probe(&key, hash).is_err() {
+                    dict.size += 1;
+                }
+                dict.force_insert(key, n.1.clone(), hash);
+            }
+        }
+
+        dict
+    }
+
+    // Consumes the dict and returns one with keys and values swapped. If
+    // several keys share a value only the one later in bucket order is kept
+    pub fn invert(self) -> Dictionary<V, K, S>
+        where V: Hash + PartialEq {
+        let mut dict: Dictionary<V, K, S> = Dictionary::with_capacity_and_hasher(self.capacity, self.hash_builder.clone());
+        for bucket in self.table {
+            if let Bucket::Entry(d) = bucket {
+                dict.insert(d.1, d.0);
+            }
+        }
+        dict
+    }
+
+    // Same as invert(), but fails instead of dropping keys that share a value
+    pub fn try_invert(self) -> Result<Dictionary<V, K, S>, InvertError>
+        where V: Hash + PartialEq {
+        let mut dict: Dictionary<V, K, S> = Dictionary::with_capacity_and_hasher(self.capacity, self.hash_builder.clone());
+        for bucket in self.table {
+            if let Bucket::Entry(d) = bucket {
+                if dict.contains(&d.1) {
+                    return Err(InvertError);
+                }
+                dict.insert(d.1, d.0);
+            }
+        }
+        Ok(dict)
+    }
+
+    // Calls f with the dict and hands the dict back unchanged, so it can be
+    // looked at partway through a chain of calls
+    pub fn inspect<F: FnOnce(&Self)>(&self, f: F) -> &Self {
+        f(self);
+        self
+    }
+
+    // Counts the keys that fall within range. This is a linear scan over the
+    // table, so it is O(n) but does not need to sort or allocate anything
+    pub fn len_in_range<R: RangeBounds<K>>(&self, range: R) -> usize
+        where K: Ord {
+        self.table.iter()
+            .filter(|b| match b {
+                Bucket::Entry(d) => range.contains(&d.0),
+                _ => false
+            })
+            .count()
+    }
+
+    // Returns a vector of (key, value) tuples for every key within range, in
+    // bucket order
+    pub fn items_in_range<R: RangeBounds<K>>(&self, range: R) -> Vec<(&K, &V)>
+        where K: Ord {
+        let mut item_vec: Vec<(&K, &V)> = Vec::new();
+        for item in self.table.iter() {
+            if let Bucket::Entry(n) = item {
+                if range.contains(&n.0) {
+                    item_vec.push((&n.0, &n.1));
+                }
+            }
+        }
+        item_vec
+    }
+
+    // Returns the entry holding the largest value, or None if the dict is
+    // empty. This is a linear scan over the table, so it is O(n)
+    pub fn max_value_entry(&self) -> Option<(&K, &V)>
+        where V: Ord {
+        self.items().into_iter().max_by(|a, b| a.1.cmp(b.1))
+    }
+
+    // Returns the entry holding the smallest value, or None if the dict is
+    // empty. This is a linear scan over the table, so it is O(n)
+    pub fn min_value_entry(&self) -> Option<(&K, &V)>
+        where V: Ord {
+        self.items().into_iter().min_by(|a, b| a.1.cmp(b.1))
+    }
+
+    // Returns the key and value stored in the bucket at index, or None if
+    // that bucket is empty, a tombstone, or out of range. Used by wrappers
+    // that remember table positions between calls
+    pub(crate) fn entry_at(&self, index: usize) -> Option<(&K, &V)> {
+        match self.table.get(index) {
+            Some(Bucket::Entry(d)) => Some((&d.0, &d.1)),
+            _ => None
+        }
+    }
+
+    // Consumes the dict and returns a vector of its values, moved out of the
+    // table rather than cloned. Values come out in bucket order
+    pub fn into_value_vec(self) -> Vec<V> {
+        self.table.into_iter()
+            .filter_map(|b| match b {
+                Bucket::Entry(d) => Some(d.1),
+                _ => None
+            })
+            .collect()
+    }
+
+    // Same as into_value_vec()
+    pub fn into_values(self) -> Vec<V> {
+        self.into_value_vec()
+    }
+
+    // Consumes the dict and returns a vector of its keys, in bucket order
+    pub fn into_keys(self) -> Vec<K> {
+        self.table.into_iter()
+            .filter_map(|b| match b {
+                Bucket::Entry(d) => Some(d.0),
+                _ => None
+            })
+            .collect()
+    }
+
+    // Consumes the dict and returns its (key, value) pairs, the reverse of
+    // from_tuples(). Like items() the order is the bucket order
+    pub fn into_tuples(self) -> Vec<(K, V)> {
+        self.table.into_iter()
+            .filter_map(|b| match b {
+                Bucket::Entry(d) => Some((d.0, d.1)),
+                _ => None
+            })
+            .collect()
+    }
+
+    // Consumes the dict and moves its entries into a SortedDictionary, for
+    // going through them in key order
+    pub fn into_sorted_dictionary(self) -> SortedDictionary<K, V>
+        where K: Ord {
+        let map: BTreeMap<K, V> = self.table.into_iter()
+            .filter_map(|b| match b {
+                Bucket::Entry(d) => Some((d.0, d.1)),
+                _ => None
+            })
+            .collect();
+        SortedDictionary::from(map)
+    }
+
+    // Returns an iterator over (key, value) references, in bucket order
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { table: &self.table, index: 0 }
+    }
+
+    // Returns every bucket of the table in order, holes and tombstones
+    // included, for checking the layout of the table itself
+    pub fn iter_buckets(&self) -> impl Iterator<Item = BucketView<'_, K, V>> {
+        self.table.iter().map(|b| match b {
+            Bucket::Entry(d) => BucketView::Entry { key: &d.0, value: &d.1, hash: d.2, index: d.3 },
+            Bucket::Empty => BucketView::Empty,
+            Bucket::Tombstone => BucketView::Tombstone
+        })
+    }
+
+    // Empties the dict back to a fresh 8 bucket table and returns an iterator
+    // that moves the old entries out. The dict is already empty when this
+    // returns, so dropping the iterator early just drops the rest
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        let table = core::mem::replace(&mut self.table, vec![Bucket::Empty; 8]);
+        self.capacity = 8;
+        self.size = 0;
+        self.generation += 1;
+        Drain { inner: IntoIter { table: table.into_iter() }, table: PhantomData }
+    }
+
+    // Returns an iterator over key references and mutable value references,
+    // in bucket order
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut { table: self.table.iter_mut() }
+    }
+}
+
+/* A spot in a dict for some key, returned by entry()
+ *
+ * Occupied holds the index of the bucket the key is already in
+ *
+ * Vacant holds the key, which has not been inserted yet
+ */
+pub enum Entry<'a, K: Clone + Hash, V: Clone, S = DefaultHashBuilder> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>)
+}
+
+pub struct OccupiedEntry<'a, K: Clone + Hash, V: Clone, S = DefaultHashBuilder> {
+    dict: &'a mut Dictionary<K, V, S>,
+    index: usize
+}
+
+pub struct VacantEntry<'a, K: Clone + Hash, V: Clone, S = DefaultHashBuilder> {
+    dict: &'a mut Dictionary<K, V, S>,
+    key: K
+}
+
+#[allow(dead_code)]
+impl<'a, K: Clone + Hash + PartialEq, V: Clone, S: BuildHasher + Clone> Entry<'a, K, V, S> {
+    // Returns the value, inserting default first if the key is missing
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(default)
+        }
+    }
+
+    // Returns the value, inserting what f returns first if the key is missing.
+    // f is only called when the key is missing
+    pub fn or_insert_with<F: FnOnce() -> V>(self, f: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(f())
+        }
+    }
+
+    // Calls f on the value if the key is already there, then hands the entry back
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(mut e) => {
+                f(e.get_mut());
+                Entry::Occupied(e)
+            },
+            Entry::Vacant(e) => Entry::Vacant(e)
+        }
+    }
+
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key()
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<'a, K: Clone + Hash + PartialEq, V: Clone, S: BuildHasher + Clone> OccupiedEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        self.entry().0
+    }
+
+    pub fn get(&self) -> &V {
+        self.entry().1
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        match &mut self.dict.table[self.index] {
+            Bucket::Entry(d) => &mut d.1,
+            _ => unreachable!("Occupied entry should point at a bucket holding an entry")
+        }
+    }
+
+    // Turns the entry into a reference to the value that lives as long as the dict borrow
+    pub fn into_mut(self) -> &'a mut V {
+        match &mut self.dict.table[self.index] {
+            Bucket::Entry(d) => &mut d.1,
+            _ => unreachable!("Occupied entry should point at a bucket holding an entry")
+        }
+    }
+
+    // Replaces the value, returning the old one
+    pub fn insert(&mut self, value: V) -> V {
+        core::mem::replace(self.get_mut(), value)
+    }
+
+    fn entry(&self) -> (&K, &V) {
+        match self.dict.entry_at(self.index) {
+            Some(e) => e,
+            None => unreachable!("Occupied entry should point at a bucket holding an entry")
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<'a, K: Clone + Hash + PartialEq, V: Clone, S: BuildHasher + Clone> VacantEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    // Inserts value under the entry's key, resizing first if needed
+    pub fn insert(self, value: V) -> &'a mut V {
+        let index = self.dict.insert_index(self.key, value);
+        match &mut self.dict.table[index] {
+            Bucket::Entry(d) => &mut d.1,
+            _ => unreachable!("Bucket an entry was just inserted into should hold it")
+        }
+    }
+}
+
+// Walks the table by index, skipping buckets that do not hold an entry
+pub struct Iter<'a, K: Clone, V: Clone> {
+    table: &'a [Bucket<K, V>],
+    index: usize
+}
+
+impl<'a, K: Clone, V: Clone> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        while let Some(bucket) = self.table.get(self.index) {
+            self.index += 1;
+            if let Bucket::Entry(d) = bucket {
+                return Some((&d.0, &d.1));
+            }
+        }
+        None
+    }
+}
+
+// Hands out mutable values from the table, skipping buckets that do not
+// hold an entry. Holding the table's iter_mut() keeps the borrow unique
+pub struct IterMut<'a, K: Clone, V: Clone> {
+    table: core::slice::IterMut<'a, Bucket<K, V>>
+}
+
+impl<'a, K: Clone, V: Clone> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a mut V)> {
+        for bucket in self.table.by_ref() {
+            if let Bucket::Entry(d) = bucket {
+                return Some((&d.0, &mut d.1));
+            }
+        }
+        None
+    }
+}
+
+// Moves entries out of the table, skipping buckets that do not hold one
+pub struct IntoIter<K: Clone, V: Clone> {
+    table: vec::IntoIter<Bucket<K, V>>
+}
+
+impl<K: Clone, V: Clone> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        for bucket in self.table.by_ref() {
+            if let Bucket::Entry(d) = bucket {
+                return Some((d.0, d.1));
+            }
+        }
+        None
+    }
+}
+
+// Moves the entries taken out by drain(), holding onto the dict's borrow
+// until it is done
+pub struct Drain<'a, K: Clone + Hash, V: Clone> {
+    inner: IntoIter<K, V>,
+    table: PhantomData<&'a mut Vec<Bucket<K, V>>>
+}
+
+impl<'a, K: Clone + Hash, V: Clone> Iterator for Drain<'a, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        self.inner.next()
+    }
+}
+
+impl<'a, K: Clone + Hash + PartialEq, V: Clone, S: BuildHasher + Clone> IntoIterator for &'a Dictionary<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<'a, K: Clone + Hash + PartialEq, V: Clone, S: BuildHasher + Clone> IntoIterator for &'a mut Dictionary<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.iter_mut()
+    }
+}
+
+impl<K: Clone + Hash + PartialEq, V: Clone, S> IntoIterator for Dictionary<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter { table: self.table.into_iter() }
+    }
+}
+
+#[allow(dead_code)]
+impl<K: Clone + Hash + PartialEq + Ord, V: Clone, S: BuildHasher + Clone> Dictionary<K, V, S> {
+    // Returns a vector of the keys in ascending order
+    pub fn keys_sorted(&self) -> Vec<&K> {
+        let mut key_vec = self.keys();
+        key_vec.sort();
+        key_vec
+    }
+
+    // Returns a vector of (key, value) tuples in ascending key order, which
+    // unlike items() is the same from run to run
+    pub fn to_sorted_vec(&self) -> Vec<(&K, &V)> {
+        self.to_sorted_vec_by(|a, b| a.0.cmp(b.0))
+    }
+}
+
+#[allow(dead_code)]
+impl<K: Clone + Hash + PartialEq, V: Clone + Ord, S: BuildHasher + Clone> Dictionary<K, V, S> {
+    // Returns a vector of the values in ascending order
+    pub fn values_sorted(&self) -> Vec<&V> {
+        let mut value_vec = self.values();
+        value_vec.sort();
+        value_vec
+    }
+}
+
+/* Diagnostics for checking how well keys are spread over the table
+ *
+ * These walk the whole table, so they are only built with the diagnostics
+ * feature and are meant for tracking down slow hashers or overfull tables
+ * rather than for calling on every operation
+ */
+#[cfg(feature = "diagnostics")]
+#[allow(dead_code)]
+impl<K: Clone + Hash + PartialEq, V: Clone, S: BuildHasher + Clone> Dictionary<K, V, S> {
+    // Counts the buckets probed past before reaching index, following the
+    // probe sequence for key_hash
+    fn probe_length(&self, key_hash: usize, index: usize) -> usize {
+        let mut probe = bucket_index(key_hash, self.capacity);
+        let mut perturb: usize = key_hash;
+        let mut length: usize = 0;
+        while probe != index {
+            probe = self.next_index(probe, &mut perturb);
+            length += 1;
+        }
+        length
+    }
+
+    fn probe_lengths(&self) -> impl Iterator<Item = usize> + '_ {
+        self.table.iter()
+            .enumerate()
+            .filter_map(move |(index, b)| match b {
+                Bucket::Entry(d) => Some(self.probe_length(d.2, index)),
+                _ => None
+            })
+    }
+
+    // Longest probe any entry needs, 0 if every entry sits in its first bucket
+    pub fn max_probe_length(&self) -> usize {
+        self.probe_lengths().max().unwrap_or(0)
+    }
+
+    // Mean probe length over every entry, 0 for an empty dict
+    pub fn average_probe_length(&self) -> f64 {
+        if self.size == 0 {
+            return 0.0;
+        }
+        self.probe_lengths().sum::<usize>() as f64 / self.size as f64
+    }
+
+    // Number of keys whose primary slot is each index of the table
+    pub fn hash_distribution(&self) -> Vec<usize> {
+        let mut distribution = vec![0; self.capacity];
+        for b in self.table.iter() {
+            if let Bucket::Entry(d) = b {
+                distribution[bucket_index(d.2, self.capacity)] += 1;
+            }
+        }
+        distribution
+    }
+
+    // Entries whose primary slot was already claimed by another entry, so a
+    // perfect hasher gives 0
+    pub fn count_hash_collisions(&self) -> usize {
+        self.hash_distribution()
+            .iter()
+            .map(|&n| n.saturating_sub(1))
+            .sum()
+    }
+}
+
+/* Reverse lookups, going from a value to the keys that map to it
+ *
+ * Values are not hashed, so these scan the whole table and are O(n). If
+ * they are needed often, invert() the dict once and look keys up in that
+ */
+#[allow(dead_code)]
+impl<K: Clone + Hash + PartialEq, V: Clone + PartialEq, S: BuildHasher + Clone> Dictionary<K, V, S> {
+    // Returns the first key in bucket order that maps to value
+    pub fn lookup_by_value(&self, value: &V) -> Option<&K> {
+        self.iter().find(|(_, v)| *v == value).map(|(k, _)| k)
+    }
+
+    // Same as keys_with_value()
+    pub fn get_all_keys_for_value(&self, value: &V) -> Vec<&K> {
+        self.keys_with_value(value)
+    }
+}
+
+/* Set operations on the keys of two dicts
+ *
+ * These walk self and look each key up in other, so they take time
+ * proportional to the size of self rather than the product of both sizes.
+ * If other is much smaller, calling the method on other instead is cheaper
+ * where the result allows it
+ */
+#[allow(dead_code)]
+impl<K: Clone + Hash + PartialEq, V: Clone, S: BuildHasher + Clone> Dictionary<K, V, S> {
+    // Returns a dict of the keys in both dicts, each mapped to its value in
+    // self and its value in other
+    pub fn intersection<'a>(&'a self, other: &'a Dictionary<K, V, S>) -> Dictionary<K, (&'a V, &'a V), S> {
+        let mut dict: Dictionary<K, (&'a V, &'a V), S> = Dictionary::with_hasher(self.hash_builder.clone());
+        for (key, value) in self.iter() {
+            if let Some((_, other_value)) = other.get_key_value(key) {
+                dict.insert(key.clone(), (value, other_value));
+            }
+        }
+        dict
+    }
+
+    // Returns the keys found in both dicts
+    pub fn keys_intersection<'a>(&'a self, other: &Dictionary<K, V, S>) -> Vec<&'a K> {
+        self.iter()
+            .filter(|(k, _)| other.contains(k))
+            .map(|(k, _)| k)
+            .collect()
+    }
+
+    // Returns a dict of the entries found in both dicts with the same value
+    pub fn value_intersection(&self, other: &Dictionary<K, V, S>) -> Dictionary<K, V, S>
+        where V: PartialEq {
+        let mut dict: Dictionary<K, V, S> = Dictionary::with_hasher(self.hash_builder.clone());
+        for (key, value) in self.iter() {
+            if other.get_key_value(key).map(|e| e.1) == Some(value) {
+                dict.insert(key.clone(), value.clone());
+            }
+        }
+        dict
+    }
+
+    // Returns a dict of the entries in self whose keys are not in other
+    pub fn difference(&self, other: &Dictionary<K, V, S>) -> Dictionary<K, V, S> {
+        let mut dict: Dictionary<K, V, S> = Dictionary::with_hasher(self.hash_builder.clone());
+        for (key, value) in self.iter() {
+            if !other.contains(key) {
+                dict.insert(key.clone(), value.clone());
+            }
+        }
+        dict
+    }
+
+    // Returns a dict of the entries whose keys are in exactly one of the
+    // two dicts, each keeping the value from the dict it came from
+    pub fn symmetric_difference(&self, other: &Dictionary<K, V, S>) -> Dictionary<K, V, S> {
+        let mut dict = self.difference(other);
+        for (key, value) in other.iter() {
+            if !self.contains(key) {
+                dict.insert(key.clone(), value.clone());
+            }
+        }
+        dict
+    }
+
+    // Works out what would have to change to turn self into other
+    pub fn diff<'a>(&'a self, other: &'a Dictionary<K, V, S>) -> DictDiff<'a, K, V>
+        where V: PartialEq {
+        let mut removed: Vec<(&K, &V)> = Vec::new();
+        let mut modified: Vec<(&K, &V, &V)> = Vec::new();
+        for (key, value) in self.iter() {
+            match other.get_key_value(key) {
+                Some((_, new)) if new != value => modified.push((key, value, new)),
+                Some(_) => (),
+                None => removed.push((key, value))
+            }
+        }
+        let added: Vec<(&K, &V)> = other.iter()
+            .filter(|(k, _)| !self.contains(k))
+            .collect();
+
+        DictDiff { added, removed, modified }
+    }
+
+    // Returns a new dict with every entry from both dicts. When a key is in
+    // both, its value is f(value in self, value in other)
+    pub fn union<F: FnMut(&V, &V) -> V>(&self, other: &Dictionary<K, V, S>, mut f: F) -> Dictionary<K, V, S> {
+        let mut dict = self.clone();
+        for (key, value) in other.iter() {
+            match dict.get_mut(key) {
+                Some(existing) => *existing = f(existing, value),
+                None => dict.insert(key.clone(), value.clone())
+            }
+        }
+        dict
+    }
+}
+
+impl<K: Clone + Hash + PartialEq, V: Clone, S: BuildHasher + Clone + Default> Default for Dictionary<K, V, S> {
+    fn default() -> Dictionary<K, V, S> {
+        Dictionary::with_hasher(S::default())
+    }
+}
+
+impl<K: Clone + Hash + PartialEq, V: Clone, S: BuildHasher + Clone + Default> FromIterator<(K, V)> for Dictionary<K, V, S> {
+    // Later values for a key overwrite earlier ones, same as insert()
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Dictionary<K, V, S> {
+        let mut dict: Dictionary<K, V, S> = Dictionary::default();
+        dict.extend(iter);
+        dict
+    }
+}
+
+impl<K: Clone + Hash + PartialEq, V: Clone, S: BuildHasher + Clone> Extend<(K, V)> for Dictionary<K, V, S> {
+    // Reserves room for the iterator's lower size bound up front, so
+    // collecting from a Vec or a range resizes at most once
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Clone + Hash + PartialEq, V: Clone, S: BuildHasher + Clone> Index<&K> for Dictionary<K, V, S> {
+    type Output = V;
+
+    // Panics if the key is missing, use get_key_value() to check first
+    fn index(&self, key: &K) -> &V {
+        match self.get_key_value(key) {
+            Some(e) => e.1,
+            None => panic!("Key does not exist")
+        }
+    }
+}
+
+// Dicts are equal when they hold the same entries, however their tables
+// are laid out
+impl<K: Clone + Hash + PartialEq, V: Clone + PartialEq, S: BuildHasher + Clone> PartialEq for Dictionary<K, V, S> {
+    fn eq(&self, other: &Dictionary<K, V, S>) -> bool {
+        self.size == other.size &&
+        self.iter().all(|(k, v)| other.get_key_value(k).map(|e| e.1) == Some(v))
+    }
+}
+
+impl<K: Clone + Hash + Eq, V: Clone + Eq, S: BuildHasher + Clone> Eq for Dictionary<K, V, S> {}
+
+// Entries are hashed one at a time and summed, so the result doesn't depend
+// on which buckets they ended up in and equal dicts always hash the same
+impl<K: Clone + Hash + PartialEq, V: Clone + Hash, S> Hash for Dictionary<K, V, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut combined: u64 = 0;
+        for bucket in self.table.iter() {
+            if let Bucket::Entry(d) = bucket {
+                let mut hasher = FnvHasher::default();
+                d.0.hash(&mut hasher);
+                d.1.hash(&mut hasher);
+                combined = combined.wrapping_add(hasher.finish());
+            }
+        }
+        state.write_usize(self.size);
+        state.write_u64(combined);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S, T> PartialEq<HashMap<K, V, T>> for Dictionary<K, V, S>
+    where K: Clone + Hash + Eq,
+          V: Clone + PartialEq,
+          S: BuildHasher + Clone,
+          T: BuildHasher {
+    fn eq(&self, other: &HashMap<K, V, T>) -> bool {
+        self.size == other.len() &&
+        self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Clone + Hash + Eq, V: Clone> From<HashMap<K, V>> for Dictionary<K, V> {
+    fn from(map: HashMap<K, V>) -> Dictionary<K, V> {
+        let mut dict: Dictionary<K, V> = Dictionary::with_capacity(Dictionary::<K, V>::capacity_for(map.len()));
+        for (key, value) in map {
+            dict.insert(key, value);
+        }
+        dict
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Clone + Hash + Eq, V: Clone, S> From<Dictionary<K, V, S>> for HashMap<K, V> {
+    fn from(dict: Dictionary<K, V, S>) -> HashMap<K, V> {
+        let mut map: HashMap<K, V> = HashMap::with_capacity(dict.size);
+        for (key, value) in dict {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl<K, V, S> fmt::Debug for Dictionary<K, V, S>
+    where K: fmt::Debug + Clone + Hash + PartialEq,
+          V: fmt::Debug + Clone,
+          S: BuildHasher + Clone {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/* Parses "key:value,key:value" into a dict, trimming whitespace around
+ * every key and value. An empty string gives an empty dict
+ *
+ * There is no escaping, so keys can't hold ':' or ',' and values can't
+ * hold ','. Everything after a pair's first ':' is its value
+ */
+impl FromStr for Dictionary<String, String> {
+    type Err = ParseDictError;
+
+    fn from_str(s: &str) -> Result<Dictionary<String, String>, ParseDictError> {
+        let mut dict: Dictionary<String, String> = Dictionary::new();
+        if s.trim().is_empty() {
+            return Ok(dict);
+        }
+
+        for pair in s.split(',') {
+            let (key, value) = match pair.find(':') {
+                Some(i) => (pair[..i].trim(), pair[i + 1..].trim()),
+                None => return Err(ParseDictError::MissingSeparator { pair: pair.to_string() })
+            };
+            if key.is_empty() {
+                return Err(ParseDictError::EmptyKey { pair: pair.to_string() });
+            }
+            dict.insert(key.to_string(), value.to_string());
+        }
+
+        Ok(dict)
+    }
+}
+
+impl<K, V, S> fmt::Display for Dictionary<K, V, S>
+    where K: fmt::Display + Clone + Hash,
+          V: fmt::Display + Clone {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut output_str = String::new();
+        output_str.push('{');
+
+        // The separator goes before every entry but the first, so nothing has
+        // to be trimmed off the end afterwards
+        let mut separator = "";
+        for k in self.table.iter() // Iterate over all buckets containing an entry
+            .filter(|v| matches!(v, Bucket::Entry(_n))) {
+            if let Bucket::Entry(d) = k {
+                write!(output_str, "{}{}: {}", separator, d.0, d.1)?;
+                separator = ", ";
+            }
         }
-        output_str.push_str("}");
+        output_str.push('}');
 
         write!(f, "{}", output_str)
     }
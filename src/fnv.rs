@@ -0,0 +1,37 @@
+use core::hash::{BuildHasherDefault, Hasher};
+
+/* state is the hash of every byte written so far
+ *
+ * This is the 64 bit FNV-1a hash. It is nowhere near as resistant to
+ * crafted keys as the std hasher, but needs nothing but core, so it is the
+ * default for dicts when the std feature is off
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct FnvHasher {
+    state: u64
+}
+
+#[allow(dead_code)]
+pub type FnvBuildHasher = BuildHasherDefault<FnvHasher>;
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+impl Default for FnvHasher {
+    fn default() -> FnvHasher {
+        FnvHasher { state: OFFSET_BASIS }
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.state ^= u64::from(*byte);
+            self.state = self.state.wrapping_mul(PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.state
+    }
+}
@@ -1,5 +1,18 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+mod bidict;
 mod dictionary;
+mod fnv;
+mod multimap;
+mod ordered;
+mod small;
+mod sorted;
+mod weighted;
 
+use alloc::vec;
+use alloc::vec::Vec;
 use dictionary::Dictionary;
 
 /* 
@@ -20,7 +33,7 @@ fn create_dict() -> Dictionary<u8, u8> {
 }
 
 #[allow(dead_code)]
-fn has_same_elements<T: PartialEq>(vec1: &Vec<T>, vec2: &Vec<T>) -> bool {
+fn has_same_elements<T: PartialEq>(vec1: &[T], vec2: &[T]) -> bool {
     for i in vec1 {
         if vec2.contains(i) {
             continue;
@@ -33,6 +46,33 @@ fn has_same_elements<T: PartialEq>(vec1: &Vec<T>, vec2: &Vec<T>) -> bool {
 #[cfg(test)]
 mod tests{
     use super::*;
+    use crate::bidict::BiDict;
+    use crate::dictionary::{AllocationError, BucketView, DictError, Entry, IntegrityError, InvertError, MergeSource, ParseDictError,
+                            ResizePolicy};
+    use crate::multimap::Multimap;
+    use crate::ordered::OrderedDictionary;
+    use crate::small::SmallDict;
+    use crate::weighted::WeightedDictionary;
+    use std::hash::{BuildHasherDefault, Hasher};
+
+    // Sends every key to the same bucket so they all share one probe chain
+    #[derive(Default)]
+    struct CollidingHasher;
+    impl Hasher for CollidingHasher {
+        fn finish(&self) -> u64 { 0 }
+        fn write(&mut self, _: &[u8]) {}
+    }
+    type Colliding = BuildHasherDefault<CollidingHasher>;
+
+    // Hashes every u8 key to its own value so first buckets are predictable
+    #[derive(Default)]
+    struct IdentityHasher(u64);
+    impl Hasher for IdentityHasher {
+        fn finish(&self) -> u64 { self.0 }
+        fn write(&mut self, _: &[u8]) {}
+        fn write_u8(&mut self, n: u8) { self.0 = n as u64 }
+    }
+    type Identity = BuildHasherDefault<IdentityHasher>;
 
     #[test]
     fn make_dict() {
@@ -45,6 +85,9 @@ mod tests{
     fn create_sized() {
         let _d: Dictionary<u8, u8> = Dictionary::with_capacity(16); 
         assert_eq!(_d.capacity(), 16);
+
+        let _r: Dictionary<u8, u8> = Dictionary::with_capacity(10);
+        assert_eq!(_r.capacity(), 16);
     }
 
     #[test]
@@ -67,7 +110,7 @@ mod tests{
         let tuples: Vec<(u8, u8)> = vec![(1, 2), (3, 4)];
         let _d: Dictionary<u8, u8> = Dictionary::from_tuples(tuples);
 
-        assert_eq!(_d.get(&1).unwrap(), 2);
+        assert_eq!(*_d.get(&1).unwrap(), 2);
     }
 
     #[test]
@@ -97,7 +140,7 @@ mod tests{
     fn lookup() {
         let _d = create_dict();
 
-        assert_eq!(_d.get(&1).unwrap(), 6);
+        assert_eq!(*_d.get(&1).unwrap(), 6);
     }
 
     #[test]
@@ -105,7 +148,7 @@ mod tests{
         let mut _d: Dictionary<u8, u8> = Dictionary::new();
         _d.insert(1, 2);
 
-        assert_eq!(_d.get(&1).unwrap(), 2);
+        assert_eq!(*_d.get(&1).unwrap(), 2);
     }
 
     #[test]
@@ -139,9 +182,19 @@ mod tests{
         
         assert!((!_d.contains(&1)) &&
         _r.is_some() &&
-        _r.unwrap() == (1, 6) &&
+        _r.unwrap() == 6 &&
         _d.size() == 4);
     }
+
+    #[test]
+    fn remove_entry() {
+        let mut _d = create_dict();
+        let _r = _d.remove_entry(&1);
+
+        assert_eq!(_r, Some((1, 6)));
+        assert!(!_d.contains(&1));
+        assert_eq!(_d.remove_entry(&1), None);
+    }
     
     #[test]
     fn down_size() {
@@ -150,20 +203,31 @@ mod tests{
         _d.remove(&1);
         _d.remove(&2);
 
-        assert_eq!(_d.capacity(), 5);
+        assert_eq!(_d.capacity(), 8);
     }
 
     #[test]
-    fn remove_panic() { 
+    fn remove_missing_key_returns_none() {
         let mut _d: Dictionary<u8, u8> = Dictionary::new();
-        _d.remove(&1);
+        assert_eq!(_d.remove(&1), None);
+        assert_eq!(_d.remove_entry(&1), None);
+        assert_eq!(_d.size(), 0);
+    }
+
+    #[test]
+    fn take() {
+        let mut _d = create_dict();
+
+        assert_eq!(_d.take(&2), Some(7));
+        assert_eq!(_d.take(&2), None);
+        assert_eq!(_d.size(), 4);
     }
 
     #[test]
     fn keys() {
         let _d = create_dict();
         let expected_keys: Vec<u8> = vec![1, 2, 3, 4, 5];
-        let keys = _d.keys().into_iter().map(|x| *x).collect(); 
+        let keys: Vec<u8> = _d.keys().into_iter().copied().collect(); 
         assert!(has_same_elements(&keys, &expected_keys));
     }
     
@@ -171,7 +235,7 @@ mod tests{
     fn values() {
         let _d = create_dict();
         let expected_values: Vec<u8> = vec![6, 7, 8, 9, 0];
-        let values = _d.values().into_iter().map(|x| *x).collect();
+        let values: Vec<u8> = _d.values().into_iter().copied().collect();
         assert!(has_same_elements(&values, &expected_values));
     }
 
@@ -180,7 +244,1906 @@ mod tests{
         let tuples: Vec<(u8, u8)> = vec![(1, 6), (2, 7), (3, 8), (4, 9), (5, 0)];
         let _t = tuples.clone();
         let _d: Dictionary<u8, u8> = Dictionary::from_tuples(_t);
-        let expected_items = _d.items().into_iter().map(|x| (*x.0, *x.1)).collect();
+        let expected_items: Vec<(u8, u8)> = _d.items().into_iter().map(|x| (*x.0, *x.1)).collect();
         assert!(has_same_elements(&expected_items, &tuples));
     }
+
+    #[test]
+    fn into_value_vec() {
+        let _d = create_dict();
+        let expected_values: Vec<u8> = vec![6, 7, 8, 9, 0];
+        let values = _d.into_value_vec();
+        assert_eq!(values.len(), 5);
+        assert!(has_same_elements(&values, &expected_values));
+    }
+
+    #[test]
+    fn len_estimate() {
+        let mut _d = create_dict();
+        assert!(_d.len_estimate() >= _d.size());
+
+        _d.remove(&1);
+        assert!(_d.len_estimate() >= 4);
+    }
+
+    #[test]
+    fn weighted_sample() {
+        let mut _w: WeightedDictionary<u8> = WeightedDictionary::new();
+        _w.insert(1, 1.0);
+        _w.insert(2, 0.0);
+        _w.insert(3, 3.0);
+
+        assert_eq!(_w.total_weight(), 4.0);
+        for i in 0..100 {
+            let picked = *_w.sample_with(i as f64 / 100.0).unwrap();
+            assert!(picked == 1 || picked == 3);
+        }
+        assert!(_w.sample_with(0.999_999_999).is_some());
+
+        let hits = (0..100).filter(|i| _w.sample_with(*i as f64 / 100.0) == Some(&3)).count();
+        assert_eq!(hits, 75);
+    }
+
+    #[test]
+    fn weighted_sample_after_remove() {
+        let mut _w: WeightedDictionary<u8> = WeightedDictionary::new();
+        assert!(_w.sample_with(0.5).is_none());
+
+        _w.insert(1, 2.0);
+        _w.insert(2, 2.0);
+        assert!(_w.sample_with(0.5).is_some());
+
+        _w.remove(&1);
+        assert_eq!(_w.sample_with(0.0), Some(&2));
+        assert_eq!(_w.sample_with(0.99), Some(&2));
+    }
+
+    #[test]
+    fn weighted_sample_out_of_range() {
+        let mut _w: WeightedDictionary<u8> = WeightedDictionary::new();
+        _w.insert(1, 0.0);
+        _w.insert(2, 1.0);
+
+        for u in [0.0, 0.5, 0.999] {
+            assert_eq!(_w.sample_with(u), Some(&2));
+        }
+        for u in [-0.5, -f64::MIN_POSITIVE, 1.0, 1.5, f64::NAN] {
+            assert_eq!(_w.sample_with(u), None);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn weighted_sample_rng() {
+        let mut _w: WeightedDictionary<u8> = WeightedDictionary::new();
+        _w.insert(1, 1.0);
+        _w.insert(2, 0.0);
+        let mut rng = rand::rngs::mock::StepRng::new(0, 1 << 60);
+        for _ in 0..10 {
+            assert_eq!(_w.sample(&mut rng), Some(&1));
+        }
+    }
+
+    #[test]
+    fn len_in_range() {
+        let _d = create_dict();
+
+        assert_eq!(_d.len_in_range(2..4), 2);
+        assert_eq!(_d.len_in_range(2..=4), 3);
+        assert_eq!(_d.len_in_range(..), 5);
+        assert_eq!(_d.len_in_range(6..), 0);
+    }
+
+    #[test]
+    fn items_in_range() {
+        let _d = create_dict();
+        let items: Vec<(u8, u8)> = _d.items_in_range(4..).into_iter().map(|x| (*x.0, *x.1)).collect();
+
+        assert_eq!(items.len(), 2);
+        assert!(has_same_elements(&items, &[(4, 9), (5, 0)]));
+    }
+
+    #[test]
+    fn get_cached() {
+        let mut _d = create_dict();
+        let hit: Result<&u8, ()> = _d.get_cached(1, |_| panic!("compute called on a hit"));
+        assert_eq!(*hit.unwrap(), 6);
+
+        let miss: Result<&u8, ()> = _d.get_cached(10, |k| Ok(*k * 2));
+        assert_eq!(*miss.unwrap(), 20);
+        assert_eq!(*_d.get(&10).unwrap(), 20);
+        assert_eq!(_d.size(), 6);
+    }
+
+    #[test]
+    fn get_cached_error() {
+        let mut _d = create_dict();
+        let failed: Result<&u8, &str> = _d.get_cached(10, |_| Err("failed"));
+
+        assert_eq!(failed, Err("failed"));
+        assert!(!_d.contains(&10));
+        assert_eq!(_d.size(), 5);
+    }
+
+    #[test]
+    fn map_values_mut() {
+        let mut _d = create_dict();
+        _d.map_values_mut(|v| *v += 1);
+
+        assert_eq!(*_d.get(&1).unwrap(), 7);
+        assert_eq!(*_d.get(&5).unwrap(), 1);
+    }
+
+    #[test]
+    fn transform() {
+        let mut _d = create_dict();
+        _d.transform(|v| *v *= 2);
+        let expected_values: Vec<u8> = vec![12, 14, 16, 18, 0];
+        let values: Vec<u8> = _d.values().into_iter().copied().collect();
+
+        assert!(has_same_elements(&values, &expected_values));
+    }
+
+    #[test]
+    #[cfg(not(feature = "robin-hood"))] // Robin Hood tables never hold tombstones
+    fn load_stats() {
+        let mut _d: Dictionary<u8, u8> = Dictionary::with_capacity(16);
+        for i in 0..8 {
+            _d.insert(i, i);
+        }
+        _d.remove(&0);
+        _d.remove(&1);
+        let stats = _d.load_stats();
+
+        assert_eq!(stats.capacity, 16);
+        assert_eq!(stats.size, 6);
+        assert_eq!(stats.tombstones, 2);
+        assert_eq!(stats.live_load, 6.0 / 16.0);
+        assert_eq!(stats.effective_load, 0.5);
+        assert_eq!(stats.fragmentation_ratio, 0.25);
+    }
+
+    #[test]
+    fn load_stats_display() {
+        let _d: Dictionary<u8, u8> = Dictionary::new();
+        let stats = _d.load_stats();
+
+        assert_eq!(stats.fragmentation_ratio, 0.0);
+        assert_eq!(format!("{}", stats),
+                   "capacity: 8, size: 0, tombstones: 0, live load: 0.000, effective load: 0.000, fragmentation: 0.000");
+    }
+
+    #[test]
+    fn inspect() {
+        let _d = create_dict();
+        let mut seen = 0;
+        let size = _d.inspect(|d| seen = d.size()).size();
+
+        assert_eq!(seen, 5);
+        assert_eq!(size, 5);
+    }
+
+    #[test]
+    fn max_min_value_entry() {
+        let _d = create_dict();
+
+        assert_eq!(_d.max_value_entry(), Some((&4, &9)));
+        assert_eq!(_d.min_value_entry(), Some((&5, &0)));
+
+        let _e: Dictionary<u8, u8> = Dictionary::new();
+        assert!(_e.max_value_entry().is_none());
+        assert!(_e.min_value_entry().is_none());
+    }
+
+    #[test]
+    fn from_zip() {
+        let _d: Dictionary<usize, char> = Dictionary::from_zip(0.., "abc".chars());
+
+        assert_eq!(_d.size(), 3);
+        assert_eq!(*_d.get(&0).unwrap(), 'a');
+        assert_eq!(*_d.get(&2).unwrap(), 'c');
+        assert!(!_d.contains(&3));
+
+        let _e: Dictionary<u8, u8> = Dictionary::from_zip(Vec::new(), vec![1, 2]);
+        assert_eq!(_e.size(), 0);
+    }
+
+    #[test]
+    fn dedup() {
+        let tuples: Vec<(u8, u8)> = vec![(1, 6), (2, 6), (3, 8), (4, 6), (5, 8), (6, 0)];
+        let mut _d: Dictionary<u8, u8> = Dictionary::from_tuples(tuples);
+        _d.dedup();
+        let mut values: Vec<u8> = _d.values().into_iter().copied().collect();
+        values.sort();
+
+        assert_eq!(_d.size(), 3);
+        assert_eq!(values, vec![0, 6, 8]);
+    }
+
+    #[test]
+    fn normalize() {
+        let tuples: Vec<(String, u8)> = vec![(" One".to_string(), 1), ("TWO".to_string(), 2),
+                                             ("two ".to_string(), 3)];
+        let _d = Dictionary::from_tuples(tuples).normalize(|k| k.trim().to_lowercase());
+
+        assert_eq!(_d.size(), 2);
+        assert_eq!(*_d.get(&"one".to_string()).unwrap(), 1);
+        let two = *_d.get(&"two".to_string()).unwrap();
+        assert!(two == 2 || two == 3);
+    }
+
+    #[test]
+    fn to_vec_map() {
+        let _d = create_dict();
+        let map = _d.to_vec_map();
+
+        assert_eq!(map.len(), 5);
+        assert!(map.iter().all(|(_, v)| v.len() == 1));
+        assert!(map.contains(&(3, vec![8])));
+    }
+
+    #[test]
+    #[cfg(not(feature = "robin-hood"))] // Robin Hood tables never hold tombstones
+    fn compact() {
+        let mut _d: Dictionary<u8, u8> = Dictionary::with_capacity(16);
+        for i in 0..8 {
+            _d.insert(i, i);
+        }
+        _d.remove(&0);
+        _d.remove(&1);
+        assert_eq!(_d.tombstone_count(), 2);
+
+        _d.compact();
+        assert_eq!(_d.tombstone_count(), 0);
+        assert_eq!(_d.capacity(), 16);
+        assert_eq!(*_d.get(&7).unwrap(), 7);
+    }
+
+    #[test]
+    #[cfg(not(feature = "robin-hood"))] // Robin Hood tables never hold tombstones
+    fn tombstone_limit() {
+        let mut _d: Dictionary<u8, u8> = Dictionary::with_capacity(16).with_tombstone_limit(1);
+        for i in 0..8 {
+            _d.insert(i, i);
+        }
+        _d.remove(&0);
+        assert_eq!(_d.tombstone_count(), 1);
+
+        _d.remove(&1);
+        assert_eq!(_d.tombstone_count(), 0);
+        assert_eq!(_d.size(), 6);
+        assert!(!_d.contains(&1));
+    }
+
+    #[test]
+    fn rebalance() {
+        let mut _d: Dictionary<u16, u16> = Dictionary::new();
+        for i in 0..200 {
+            _d.insert(i, i * 2);
+        }
+        _d.remove(&10);
+        _d.rebalance();
+
+        assert_eq!(_d.size(), 199);
+        assert_eq!(_d.tombstone_count(), 0);
+        assert!(!_d.contains(&10));
+        for i in (0..200).filter(|i| *i != 10) {
+            assert_eq!(*_d.get(&i).unwrap(), i * 2);
+        }
+    }
+
+    #[test]
+    fn rebalance_evens_out_probes() {
+        // Keys under 32 probe linearly from bucket key % 16 with this hasher,
+        // so an entry's displacement can be read off its bucket
+        fn robin_hood_ordered(_d: &Dictionary<u8, u8, Identity>) -> bool {
+            let capacity = _d.capacity();
+            let displacement = |hash: usize, index: usize| (index + capacity - hash % capacity) % capacity;
+            let buckets: Vec<BucketView<u8, u8>> = _d.iter_buckets().collect();
+
+            // No bucket between an entry's first one and its own may hold an
+            // entry that has come less far than it had by then
+            buckets.iter().enumerate().all(|(index, b)| match b {
+                BucketView::Entry { hash, .. } => (0..displacement(*hash, index)).all(|distance| {
+                    let at = (hash + distance) % capacity;
+                    match buckets[at] {
+                        BucketView::Entry { hash: h, .. } => displacement(h, at) >= distance,
+                        _ => false
+                    }
+                }),
+                _ => true
+            })
+        }
+
+        // 17 and 18 have to walk past 1 to 5, which all sit in their own buckets
+        let mut _d: Dictionary<u8, u8, Identity> = Dictionary::with_capacity_and_hasher(16, Identity::default());
+        for i in [1, 2, 3, 4, 5, 17, 18] {
+            _d.insert(i, i);
+        }
+        if !cfg!(feature = "robin-hood") {
+            assert!(!robin_hood_ordered(&_d));
+        }
+
+        _d.rebalance();
+        assert!(robin_hood_ordered(&_d));
+        assert!(_d.verify_integrity().is_empty());
+        for i in [1, 2, 3, 4, 5, 17, 18] {
+            assert_eq!(*_d.get(&i).unwrap(), i);
+        }
+    }
+
+    #[test]
+    fn estimate_memory_bytes() {
+        let _d: Dictionary<u8, u8> = Dictionary::with_capacity(16);
+        let _e: Dictionary<u8, u8> = Dictionary::with_capacity(32);
+        let bytes = _d.estimate_memory_bytes();
+
+        assert!(bytes > 16 * 2);
+        assert_eq!(_e.estimate_memory_bytes() - bytes, bytes - std::mem::size_of::<Dictionary<u8, u8>>());
+    }
+
+    #[test]
+    fn into_sorted_dictionary() {
+        let _s = create_dict().into_sorted_dictionary();
+        let keys: Vec<u8> = _s.keys().copied().collect();
+
+        assert_eq!(keys, vec![1, 2, 3, 4, 5]);
+        assert_eq!(_s.get(&3), Some(&8));
+
+        let _d = _s.into_dictionary();
+        assert_eq!(_d.size(), 5);
+        assert_eq!(*_d.get(&5).unwrap(), 0);
+    }
+
+    #[test]
+    fn resize_policy() {
+        let mut _g: Dictionary<u8, u8> = Dictionary::with_capacity(8)
+            .with_resize_policy(ResizePolicy::GoldenRatio);
+        let mut _f: Dictionary<u8, u8> = Dictionary::with_capacity(8)
+            .with_resize_policy(ResizePolicy::FixedStep(4));
+        let mut _c: Dictionary<u8, u8> = Dictionary::with_capacity(8)
+            .with_resize_policy(ResizePolicy::Custom(|c| c * 3));
+        for i in 0..7 {
+            _g.insert(i, i);
+            _f.insert(i, i);
+            _c.insert(i, i);
+        }
+
+        // 13, 12 and 24 buckets, rounded up to powers of two
+        assert_eq!(_g.capacity(), 16);
+        assert_eq!(_f.capacity(), 16);
+        assert_eq!(_c.capacity(), 32);
+        for i in 0..7 {
+            assert_eq!(*_f.get(&i).unwrap(), i);
+        }
+
+        _f.remove(&0);
+        _f.remove(&1);
+        _f.remove(&2);
+        assert_eq!(_f.capacity(), 8);
+    }
+
+    #[test]
+    fn key_hashes() {
+        let _d = create_dict();
+        let hashes = _d.key_hashes();
+        let mut distinct: Vec<usize> = hashes.iter().map(|h| h.1).collect();
+        distinct.sort();
+        distinct.dedup();
+
+        assert_eq!(hashes.len(), 5);
+        assert_eq!(distinct.len(), 5);
+    }
+
+    #[test]
+    fn verify_integrity() {
+        let mut _d = create_dict();
+        assert!(_d.verify_integrity().is_empty());
+
+        _d.remove(&1);
+        _d.rebalance();
+        assert!(_d.verify_integrity().is_empty());
+    }
+
+    #[test]
+    fn verify_integrity_hash_mismatch() {
+        use std::cell::Cell;
+        use std::fmt;
+        use std::hash::Hash;
+        use std::rc::Rc;
+
+        // Hashes to whatever the shared cell holds, so the test can change a
+        // key's hash after it has been inserted
+        #[derive(Clone)]
+        struct Shifty(u8, Rc<Cell<u8>>);
+        impl PartialEq for Shifty {
+            fn eq(&self, other: &Shifty) -> bool { self.0 == other.0 }
+        }
+        impl Hash for Shifty {
+            fn hash<H: Hasher>(&self, state: &mut H) { state.write_u8(self.1.get()) }
+        }
+        impl fmt::Debug for Shifty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "Shifty({})", self.0) }
+        }
+
+        let hash = Rc::new(Cell::new(3));
+        let mut _d: Dictionary<Shifty, u8, Identity> = Dictionary::with_capacity_and_hasher(16, Identity::default());
+        _d.insert(Shifty(1, hash.clone()), 1);
+        assert!(_d.verify_integrity().is_empty());
+
+        hash.set(5);
+        assert_eq!(_d.verify_integrity(), vec![IntegrityError::HashMismatch {
+            key_repr: "Shifty(1)".to_string(),
+            stored_hash: 3,
+            computed_hash: 5
+        }]);
+    }
+
+    #[test]
+    fn integrity_error_display() {
+        let error = IntegrityError::SizeCountMismatch { stored: 3, actual: 2 };
+        assert_eq!(format!("{}", error), "size is 3 but the table holds 2 entries");
+
+        let error = IntegrityError::HashMismatch { key_repr: "7".to_string(), stored_hash: 1, computed_hash: 2 };
+        assert_eq!(format!("{}", error), "key 7 has stored hash 1 but hashes to 2");
+
+        let error = IntegrityError::IndexMismatch { stored_index: 4, actual_index: 6 };
+        assert_eq!(format!("{}", error), "entry in bucket 6 has stored index 4");
+
+        let error = IntegrityError::CapacityMismatch { stored: 8, table_len: 16 };
+        assert_eq!(format!("{}", error), "capacity is 8 but the table has 16 buckets");
+    }
+
+    #[test]
+    #[cfg(feature = "prefetch")]
+    fn prefetch() {
+        let _d = create_dict();
+        _d.prefetch(&3);
+        _d.prefetch(&10);
+
+        assert_eq!(*_d.get(&3).unwrap(), 8);
+    }
+
+    #[test]
+    fn bulk_get() {
+        let _d = create_dict();
+        let values = _d.bulk_get(&[&3, &10, &1]);
+
+        assert_eq!(values, vec![Some(&8), None, Some(&6)]);
+        assert!(_d.bulk_get(&[]).is_empty());
+    }
+
+    #[test]
+    fn symmetric_merge() {
+        let _d = create_dict();
+        let _o: Dictionary<u8, char> = Dictionary::from_tuples(vec![(5, 'a'), (6, 'b')]);
+        let merged = _d.symmetric_merge(&_o);
+
+        assert_eq!(merged.size(), 6);
+        assert_eq!(*merged.get(&1).unwrap(), MergeSource::Left(6));
+        assert_eq!(*merged.get(&5).unwrap(), MergeSource::Both(0, 'a'));
+        assert_eq!(*merged.get(&6).unwrap(), MergeSource::Right('b'));
+    }
+
+    #[test]
+    fn auto_shard() {
+        let mut _d: Dictionary<u16, u16> = Dictionary::new();
+        for i in 0..100 {
+            _d.insert(i, i);
+        }
+        let shards = _d.auto_shard(3);
+
+        assert_eq!(shards.len(), 3);
+        assert_eq!(shards.iter().map(|s| s.size()).sum::<usize>(), 100);
+        for (i, shard) in shards.iter().enumerate() {
+            assert!(shard.key_hashes().iter().all(|h| h.1 % 3 == i));
+            assert!(shard.verify_integrity().is_empty());
+        }
+    }
+
+    #[test]
+    fn capacity_for() {
+        for size in 0..50 {
+            let capacity = Dictionary::<u8, u8>::capacity_for(size);
+            assert!(2 * (capacity / 3) >= size);
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "robin-hood"))] // Robin Hood tables never hold tombstones
+    fn reclaim_tombstones() {
+        let mut _d: Dictionary<u16, u16> = Dictionary::new();
+        for i in 0..100 {
+            _d.insert(i, i);
+        }
+        for i in 0..10 {
+            _d.remove(&i);
+        }
+        let before = _d.tombstone_count();
+        let reclaimed = _d.reclaim_tombstones();
+
+        assert!(reclaimed > 0);
+        assert_eq!(_d.tombstone_count(), before - reclaimed);
+        assert_eq!(_d.size(), 90);
+        assert!(_d.verify_integrity().is_empty());
+        for i in 10..100 {
+            assert_eq!(*_d.get(&i).unwrap(), i);
+        }
+        assert!(!_d.contains(&0));
+    }
+
+    #[test]
+    fn into_multimap() {
+        let _m = create_dict().into_multimap();
+
+        assert_eq!(_m.size(), 5);
+        assert_eq!(*_m.get(&2).unwrap(), vec![7]);
+    }
+
+    #[test]
+    fn into_multimap_from_pairs() {
+        let pairs: Vec<(u8, u8)> = vec![(1, 6), (2, 7), (1, 8), (1, 9)];
+        let _m = Dictionary::into_multimap_from_pairs(pairs);
+
+        assert_eq!(_m.size(), 2);
+        assert_eq!(*_m.get(&1).unwrap(), vec![6, 8, 9]);
+        assert_eq!(*_m.get(&2).unwrap(), vec![7]);
+    }
+
+    #[test]
+    fn map_collect() {
+        let _d = create_dict();
+        let formatted: Vec<String> = _d.map_collect(|k, v| format!("{}={}", k, v));
+        let keys = _d.map_collect::<std::collections::HashSet<_>, _, _>(|k, _| *k);
+
+        assert_eq!(formatted.len(), 5);
+        assert!(formatted.contains(&"3=8".to_string()));
+        assert_eq!(keys, (1..=5).collect());
+    }
+
+    #[test]
+    fn iter() {
+        let mut _d = create_dict();
+        _d.remove(&3);
+        let mut items: Vec<(u8, u8)> = Vec::new();
+        for (k, v) in &_d {
+            items.push((*k, *v));
+        }
+
+        assert_eq!(items.len(), 4);
+        assert!(has_same_elements(&items, &[(1, 6), (2, 7), (4, 9), (5, 0)]));
+        assert_eq!(_d.iter().count(), 4);
+    }
+
+    #[test]
+    fn into_iter() {
+        let _d = create_dict();
+        let items: Vec<(u8, u8)> = _d.into_iter().collect();
+
+        assert_eq!(items.len(), 5);
+        assert!(has_same_elements(&items, &[(1, 6), (2, 7), (3, 8), (4, 9), (5, 0)]));
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut _d = create_dict();
+        _d.remove(&3);
+        for (k, v) in _d.iter_mut() {
+            *v += *k;
+        }
+
+        assert_eq!(*_d.get(&1).unwrap(), 7);
+        assert_eq!(*_d.get(&5).unwrap(), 5);
+        assert_eq!(_d.iter_mut().count(), 4);
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut _d = create_dict();
+        if let Some(v) = _d.get_mut(&2) {
+            *v += 10;
+        }
+
+        assert_eq!(*_d.get(&2).unwrap(), 17);
+        assert!(_d.get_mut(&10).is_none());
+    }
+
+    #[test]
+    fn entry() {
+        let mut _d = create_dict();
+        *_d.entry(1).or_insert(0) += 1;
+        *_d.entry(10).or_insert(0) += 1;
+        _d.entry(2).and_modify(|v| *v = 20).or_insert(0);
+        _d.entry(11).and_modify(|v| *v = 20).or_insert_with(|| 30);
+
+        assert_eq!(*_d.get(&1).unwrap(), 7);
+        assert_eq!(*_d.get(&10).unwrap(), 1);
+        assert_eq!(*_d.get(&2).unwrap(), 20);
+        assert_eq!(*_d.get(&11).unwrap(), 30);
+        assert_eq!(_d.size(), 7);
+    }
+
+    #[test]
+    fn entry_variants() {
+        let mut _d = create_dict();
+        match _d.entry(3) {
+            Entry::Occupied(mut e) => {
+                assert_eq!(*e.key(), 3);
+                assert_eq!(e.insert(1), 8);
+                assert_eq!(*e.get(), 1);
+            },
+            Entry::Vacant(_) => panic!("key 3 should be occupied")
+        }
+        assert!(matches!(_d.entry(6), Entry::Vacant(_)));
+    }
+
+    #[test]
+    #[cfg(not(feature = "robin-hood"))] // Robin Hood tables never hold tombstones
+    fn insert_after_tombstone() {
+        let mut _d: Dictionary<u16, u16> = Dictionary::with_capacity(64);
+        for i in 0..40 {
+            _d.insert(i, i);
+        }
+        for i in 0..5 {
+            _d.remove(&i);
+        }
+        assert_eq!(_d.tombstone_count(), 5);
+        for i in 5..10 {
+            _d.insert(i, i + 1);
+        }
+
+        assert_eq!(_d.items().len(), 35);
+        assert_eq!(*_d.get(&7).unwrap(), 8);
+    }
+
+    #[test]
+    fn insert_overwrite() {
+        let mut _d: Dictionary<u8, u8> = Dictionary::with_capacity(8);
+        for _ in 0..20 {
+            _d.insert(1, 2);
+        }
+        _d.insert(1, 3);
+
+        assert_eq!(_d.size(), 1);
+        assert_eq!(_d.capacity(), 8);
+        assert_eq!(*_d.get(&1).unwrap(), 3);
+        assert!(_d.verify_integrity().is_empty());
+    }
+
+    #[test]
+    fn from_vecs_sizes() {
+        for n in [1usize, 2, 7, 64].iter() {
+            let keys: Vec<usize> = (0..*n).collect();
+            let values: Vec<usize> = (0..*n).map(|i| i * 10).collect();
+            let capacity = Dictionary::<usize, usize>::capacity_for(*n);
+            let _d = Dictionary::from_vecs(keys, values);
+
+            assert_eq!(_d.size(), *n);
+            assert_eq!(_d.capacity(), capacity);
+            assert_eq!(*_d.get(&(n - 1)).unwrap(), (n - 1) * 10);
+        }
+    }
+
+    #[test]
+    fn is_empty() {
+        let mut _d: Dictionary<u8, u8> = Dictionary::new();
+        assert!(_d.is_empty());
+
+        _d.insert(1, 2);
+        assert!(!_d.is_empty());
+
+        _d.remove(&1);
+        assert!(_d.is_empty());
+    }
+
+    #[test]
+    fn retain() {
+        let mut _d = create_dict();
+        _d.retain(|k, v| {
+            *v += 1;
+            k % 2 == 1
+        });
+
+        assert_eq!(_d.size(), 3);
+        assert!(!_d.contains(&2));
+        assert_eq!(*_d.get(&3).unwrap(), 9);
+        assert!(_d.verify_integrity().is_empty());
+    }
+
+    #[test]
+    fn retain_all_or_none() {
+        let mut _d: Dictionary<u16, u16> = Dictionary::new();
+        for i in 0..20 {
+            _d.insert(i, i);
+        }
+        let mut _e: Dictionary<u16, u16> = Dictionary::with_capacity(64);
+        _e.insert(1, 1);
+        _e.retain(|_, _| true);
+        assert_eq!(_e.capacity(), 64);
+
+        let capacity = _d.capacity();
+        _d.retain(|_, _| true);
+        assert_eq!(_d.size(), 20);
+        assert_eq!(_d.capacity(), capacity);
+
+        _d.retain(|_, _| false);
+        assert!(_d.is_empty());
+        assert!(_d.items().is_empty());
+        assert!(_d.verify_integrity().is_empty());
+        _d.insert(1, 1);
+        assert_eq!(*_d.get(&1).unwrap(), 1);
+    }
+
+    #[test]
+    fn drain() {
+        let mut _d = create_dict();
+        let items: Vec<(u8, u8)> = _d.drain().collect();
+
+        assert_eq!(items.len(), 5);
+        assert!(has_same_elements(&items, &[(1, 6), (2, 7), (3, 8), (4, 9), (5, 0)]));
+        assert!(_d.is_empty());
+        assert_eq!(_d.capacity(), 8);
+    }
+
+    #[test]
+    fn drain_dropped_early() {
+        let mut _d = create_dict();
+        let first = _d.drain().next();
+
+        assert!(first.is_some());
+        assert!(_d.is_empty());
+        assert!(_d.items().is_empty());
+        _d.insert(1, 2);
+        assert_eq!(*_d.get(&1).unwrap(), 2);
+    }
+
+    #[test]
+    fn get_key_value() {
+        let _d = create_dict();
+
+        assert_eq!(_d.get_key_value(&4), Some((&4, &9)));
+        assert_eq!(_d.get_key_value(&10), None);
+    }
+
+    #[test]
+    fn get_missing() {
+        let _d = create_dict();
+
+        assert_eq!(_d.get(&1), Some(&6));
+        assert_eq!(_d.get(&10), None);
+    }
+
+    #[test]
+    fn debug() {
+        let _d: Dictionary<&str, u8> = Dictionary::from_tuples(vec![("a", 1)]);
+        let _e: Dictionary<u8, u8> = Dictionary::new();
+
+        assert_eq!(format!("{:?}", _d), "{\"a\": 1}");
+        assert_eq!(format!("{:?}", _e), "{}");
+    }
+
+    #[test]
+    #[cfg(not(feature = "robin-hood"))] // Robin Hood tables never hold tombstones
+    fn debug_table() {
+        let mut _d: Dictionary<u8, u8> = Dictionary::with_capacity(16);
+        for i in 0..8 {
+            _d.insert(i, i);
+        }
+        _d.remove(&0);
+        let table = _d.debug_table();
+
+        assert_eq!(table.lines().count(), 16);
+        assert_eq!(table.matches("Tombstone").count(), 1);
+        assert_eq!(table.matches("Empty").count(), 8);
+    }
+
+    #[test]
+    fn default() {
+        let _d: Dictionary<String, Vec<u8>> = Dictionary::default();
+
+        assert!(_d.is_empty());
+        assert_eq!(_d.capacity(), 8);
+    }
+
+    #[test]
+    fn from_iter() {
+        let _d: Dictionary<u8, u8> = vec![(1, 2), (3, 4), (1, 5)].into_iter().collect();
+        let _e: Dictionary<u8, u8> = std::iter::empty().collect();
+
+        assert_eq!(_d.size(), 2);
+        assert_eq!(_d.get(&1), Some(&5));
+        assert!(_e.is_empty());
+        assert_eq!(_e.capacity(), 8);
+    }
+
+    #[test]
+    fn extend() {
+        let mut _d = create_dict();
+        let capacity = _d.capacity();
+        _d.extend((3..20).map(|i| (i, i * 2)));
+
+        assert_eq!(_d.size(), 19);
+        assert!(_d.capacity() > capacity);
+        assert_eq!(_d.get(&1), Some(&6));
+        assert_eq!(_d.get(&3), Some(&6));
+        assert_eq!(_d.get(&19), Some(&38));
+    }
+
+    #[test]
+    fn extend_from_dict() {
+        let mut _d = create_dict();
+        let _o: Dictionary<u8, u8> = (5..20).map(|i| (i, i)).collect();
+        _d.extend_from_dict(&_o);
+
+        assert_eq!(_d.size(), 19);
+        assert_eq!(_d.get(&5), Some(&5));
+        assert_eq!(_d.get(&2), Some(&7));
+        assert_eq!(_o.size(), 15);
+    }
+
+    #[test]
+    fn index() {
+        let _d = create_dict();
+
+        assert_eq!(_d[&1] + _d[&2], 13);
+    }
+
+    #[test]
+    #[should_panic(expected = "Key does not exist")]
+    fn index_missing() {
+        let _d = create_dict();
+        let _v = _d[&10];
+    }
+
+    #[test]
+    fn eq() {
+        let mut _d = create_dict();
+        let mut _e: Dictionary<u8, u8> = Dictionary::with_capacity(64);
+        for (k, v) in [(5, 0), (4, 9), (3, 8), (2, 7), (1, 6), (6, 1)].iter() {
+            _e.insert(*k, *v);
+        }
+        assert_ne!(_d, _e);
+
+        _e.remove(&6);
+        _e.compact();
+        assert_eq!(_d, _e);
+
+        _d.insert(1, 1);
+        assert_ne!(_d, _e);
+    }
+
+    #[test]
+    fn clone() {
+        let _d = create_dict();
+        let mut _c = _d.clone();
+        _c.insert(1, 1);
+        _c.remove(&2);
+
+        assert_eq!(_d.get(&1), Some(&6));
+        assert!(_d.contains(&2));
+        assert_eq!(_d.size(), 5);
+        assert_eq!(_c.size(), 4);
+    }
+
+    #[test]
+    fn merge() {
+        let mut _d = create_dict();
+        let _o: Dictionary<u8, u8> = (10..30).map(|i| (i, i)).collect();
+        _d.merge(_o);
+
+        assert_eq!(_d.size(), 25);
+        assert_eq!(_d.capacity(), Dictionary::<u8, u8>::capacity_for(25));
+        assert_eq!(_d.get(&1), Some(&6));
+        assert_eq!(_d.get(&29), Some(&29));
+
+        _d.merge(Dictionary::new());
+        assert_eq!(_d.size(), 25);
+    }
+
+    #[test]
+    fn merge_from() {
+        let mut _d = create_dict();
+        let _o: Dictionary<u8, u8> = (1..=5).map(|i| (i, 100)).collect();
+        _d.merge_from(&_o);
+
+        assert_eq!(_d.size(), 5);
+        assert!(_d.values().iter().all(|v| **v == 100));
+
+        let _c = _d.clone();
+        _d.merge_from(&_c);
+        assert_eq!(_d, _c);
+    }
+
+    #[test]
+    fn map_values() {
+        let _d = create_dict();
+        let _m: Dictionary<u8, String> = _d.map_values(|v| v.to_string());
+
+        assert_eq!(_m.size(), 5);
+        assert_eq!(_m.capacity(), _d.capacity());
+        assert_eq!(_m.get(&4), Some(&"9".to_string()));
+        assert!(_m.verify_integrity().is_empty());
+    }
+
+    #[test]
+    fn map_values_in_place() {
+        let mut _d = create_dict();
+        _d.map_values_in_place(|v| *v *= 2);
+
+        assert_eq!(_d.get(&3), Some(&16));
+    }
+
+    #[test]
+    fn map_keys() {
+        let _d = create_dict();
+
+        assert_eq!(_d.map_keys(|k| *k), _d);
+
+        let _m: Dictionary<u8, u8> = _d.map_keys(|k| k / 2);
+        assert_eq!(_m.size(), 3);
+        assert_eq!(_m.get(&0), Some(&6));
+        assert!(_m.get(&1) == Some(&7) || _m.get(&1) == Some(&8));
+        assert!(_m.verify_integrity().is_empty());
+    }
+
+    #[test]
+    fn invert() {
+        let _i = create_dict().invert();
+
+        assert_eq!(_i.size(), 5);
+        assert_eq!(_i.get(&9), Some(&4));
+
+        let _d: Dictionary<u8, u8> = Dictionary::from_tuples(vec![(1, 6), (2, 6), (3, 7)]);
+        let _i = _d.invert();
+        assert_eq!(_i.size(), 2);
+        assert!(_i.get(&6) == Some(&1) || _i.get(&6) == Some(&2));
+    }
+
+    #[test]
+    fn try_invert() {
+        assert!(create_dict().try_invert().is_ok());
+
+        let _d: Dictionary<u8, u8> = Dictionary::from_tuples(vec![(1, 6), (2, 6), (3, 7)]);
+        assert_eq!(_d.try_invert().err(), Some(InvertError));
+    }
+
+    #[test]
+    fn keys_values_sorted() {
+        let _d = create_dict();
+
+        assert_eq!(_d.keys_sorted(), vec![&1, &2, &3, &4, &5]);
+        assert_eq!(_d.values_sorted(), vec![&0, &6, &7, &8, &9]);
+    }
+
+    #[test]
+    fn items_sorted_by_key() {
+        let _d = create_dict();
+        let items = _d.items_sorted_by_key(|k| std::cmp::Reverse(*k));
+
+        assert_eq!(items[0], (&5, &0));
+        assert_eq!(items[4], (&1, &6));
+    }
+
+    #[test]
+    fn contains_value() {
+        let _d = create_dict();
+
+        assert!(_d.contains_value(&9));
+        assert!(!_d.contains_value(&1));
+    }
+
+    #[test]
+    fn keys_with_value() {
+        let _d: Dictionary<u8, u8> = Dictionary::from_tuples(vec![(1, 6), (2, 6), (3, 7)]);
+        let keys: Vec<u8> = _d.keys_with_value(&6).into_iter().copied().collect();
+
+        assert_eq!(keys.len(), 2);
+        assert!(has_same_elements(&keys, &[1, 2]));
+        assert!(_d.keys_with_value(&0).is_empty());
+    }
+
+    #[test]
+    fn sub_dict() {
+        let _d = create_dict();
+        let _s = _d.sub_dict(&[1, 3, 10]);
+
+        assert_eq!(_s.size(), 2);
+        assert_eq!(_s.get(&1), Some(&6));
+        assert_eq!(_s.get(&3), Some(&8));
+        assert!(_d.sub_dict(&[]).is_empty());
+    }
+
+    #[test]
+    fn except_keys() {
+        let _d = create_dict();
+        let _e = _d.except_keys(&[1, 3, 10]);
+
+        assert_eq!(_e.size(), 3);
+        assert!(!_e.contains(&1));
+        assert_eq!(_e.get(&5), Some(&0));
+    }
+
+    #[test]
+    fn partition() {
+        let (_odd, _even) = create_dict().partition(|k, _| k % 2 == 1);
+
+        assert_eq!(_odd.size(), 3);
+        assert_eq!(_even.size(), 2);
+        assert_eq!(_odd.get(&5), Some(&0));
+        assert_eq!(_even.get(&4), Some(&9));
+        assert!(_odd.verify_integrity().is_empty());
+        assert!(_even.verify_integrity().is_empty());
+    }
+
+    #[test]
+    fn partition_ref() {
+        let _d = create_dict();
+        let (_big, _small) = _d.partition_ref(|_, v| *v > 7);
+
+        assert_eq!(_big.size(), 2);
+        assert_eq!(_small.size(), 3);
+        assert_eq!(_big.capacity(), Dictionary::<u8, u8>::capacity_for(2));
+        assert_eq!(_d.size(), 5);
+    }
+
+    #[test]
+    fn try_insert() {
+        let mut _d = create_dict();
+
+        assert_eq!(_d.try_insert(1, 100), Err(100));
+        assert_eq!(_d.get(&1), Some(&6));
+        assert_eq!(_d.try_insert(6, 100), Ok(()));
+        assert_eq!(_d.get(&6), Some(&100));
+        assert_eq!(_d.size(), 6);
+
+        for i in 7..40 {
+            assert_eq!(_d.try_insert(i, i), Ok(()));
+        }
+        assert_eq!(_d.size(), 39);
+        assert!(_d.verify_integrity().is_empty());
+    }
+
+    #[test]
+    fn update_with() {
+        let mut _d = create_dict();
+
+        assert!(_d.update_with(&2, |v| *v *= 3));
+        assert_eq!(_d.get(&2), Some(&21));
+        assert!(!_d.update_with(&10, |v| *v *= 3));
+        assert!(!_d.contains(&10));
+    }
+
+    #[test]
+    fn update_or_insert_with() {
+        let mut _d: Dictionary<&str, u8> = Dictionary::new();
+        for word in ["a", "b", "a", "a"].iter() {
+            _d.update_or_insert_with(word, |v| *v += 1, || 1);
+        }
+
+        assert_eq!(_d.get(&"a"), Some(&3));
+        assert_eq!(_d.get(&"b"), Some(&1));
+    }
+
+    #[test]
+    fn shrink_to_fit() {
+        let mut _d: Dictionary<u8, u8> = Dictionary::with_capacity(100);
+        for i in 0..10 {
+            _d.insert(i, i);
+        }
+        _d.shrink_to_fit();
+
+        assert_eq!(_d.capacity(), Dictionary::<u8, u8>::capacity_for(10));
+        assert_eq!(_d.size(), 10);
+        for i in 0..10 {
+            assert_eq!(_d.get(&i), Some(&i));
+        }
+    }
+
+    #[test]
+    fn shrink_to() {
+        let mut _d: Dictionary<u8, u8> = Dictionary::with_capacity(100);
+        for i in 0..10 {
+            _d.insert(i, i);
+        }
+        _d.shrink_to(40);
+        assert_eq!(_d.capacity(), 64);
+
+        _d.shrink_to(2);
+        assert_eq!(_d.capacity(), Dictionary::<u8, u8>::capacity_for(10));
+
+        _d.shrink_to(80);
+        assert_eq!(_d.capacity(), Dictionary::<u8, u8>::capacity_for(10));
+        assert_eq!(_d.items().len(), 10);
+    }
+
+    #[test]
+    fn reserve() {
+        let mut _d = create_dict();
+        _d.reserve(50);
+        let capacity = _d.capacity();
+        assert_eq!(capacity, Dictionary::<u8, u8>::capacity_for(55));
+
+        for i in 10..60 {
+            _d.insert(i, i);
+        }
+        assert_eq!(_d.capacity(), capacity);
+
+        _d.reserve(0);
+        assert_eq!(_d.capacity(), capacity);
+    }
+
+    #[test]
+    fn try_reserve() {
+        let mut _d = create_dict();
+
+        assert_eq!(_d.try_reserve(20), Ok(()));
+        assert_eq!(_d.capacity(), Dictionary::<u8, u8>::capacity_for(25));
+        assert_eq!(_d.get(&3), Some(&8));
+        assert_eq!(_d.try_reserve(usize::MAX), Err(AllocationError));
+        assert_eq!(_d.try_reserve(usize::MAX / 4), Err(AllocationError));
+        assert_eq!(_d.size(), 5);
+    }
+
+    #[test]
+    #[cfg(not(feature = "robin-hood"))] // Robin Hood tables never hold tombstones
+    fn load_factor_and_tombstone_ratio() {
+        let mut _d: Dictionary<u8, u8> = Dictionary::with_capacity(16);
+        for i in 0..8 {
+            _d.insert(i, i);
+        }
+        _d.remove(&0);
+        _d.remove(&1);
+
+        assert_eq!(_d.load_factor(), 6.0 / 16.0);
+        assert_eq!(_d.tombstone_ratio(), 2.0 / 16.0);
+    }
+
+    #[test]
+    #[cfg(not(feature = "robin-hood"))] // Robin Hood tables never hold tombstones
+    fn stats() {
+        let mut _d: Dictionary<u8, u8> = Dictionary::with_capacity(16);
+        for i in 0..8 {
+            _d.insert(i, i);
+        }
+        _d.remove(&0);
+        let stats = _d.stats();
+
+        assert_eq!(stats.size, 7);
+        assert_eq!(stats.capacity, 16);
+        assert_eq!(stats.tombstone_count, 1);
+        assert_eq!(stats.tombstone_ratio, 1.0 / 16.0);
+        assert_eq!(format!("{}", stats),
+                   "size: 7, capacity: 16, load factor: 0.438, tombstones: 1, tombstone ratio: 0.062");
+    }
+
+    #[test]
+    fn rehash() {
+        let mut _d: Dictionary<u8, u8> = Dictionary::with_capacity(16);
+        for i in 0..8 {
+            _d.insert(i, i);
+        }
+        _d.remove(&0);
+        _d.rehash();
+
+        assert_eq!(_d.tombstone_count(), 0);
+        assert_eq!(_d.capacity(), 16);
+        assert_eq!(_d.size(), 7);
+    }
+
+    #[test]
+    fn custom_hasher() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+        type Fixed = BuildHasherDefault<DefaultHasher>;
+
+        let mut _d: Dictionary<&str, u8, Fixed> = Dictionary::with_hasher(Fixed::default());
+        _d.insert("a", 1);
+        _d.insert("b", 2);
+        assert_eq!(_d.get(&"a"), Some(&1));
+        assert_eq!(_d.remove(&"b"), Some(2));
+
+        let mut other: Dictionary<&str, u8, Fixed> = Dictionary::with_capacity_and_hasher(4, Fixed::default());
+        other.insert("a", 1);
+        assert_eq!(_d, other);
+
+        let collected: Dictionary<&str, u8, Fixed> = vec![("c", 3)].into_iter().collect();
+        assert_eq!(collected[&"c"], 3);
+    }
+
+    #[test]
+    #[cfg(not(feature = "robin-hood"))] // Robin Hood tables never hold tombstones
+    fn insert_reuses_first_tombstone() {
+        let mut _d: Dictionary<u8, u8, Colliding> = Dictionary::with_capacity_and_hasher(12, Colliding::default());
+        for i in 0..7 {
+            _d.insert(i, i);
+        }
+        _d.remove(&0);
+        _d.remove(&1);
+        _d.insert(7, 7);
+
+        assert_eq!(_d.entry_at(0), Some((&7, &7)));
+        assert_eq!(_d.tombstone_count(), 1);
+        assert_eq!(_d.get(&6), Some(&6));
+        assert_eq!(_d.get(&7), Some(&7));
+    }
+
+    #[test]
+    fn dict_errors() {
+        assert_eq!(Dictionary::<u8, u8>::try_with_capacity(0).err(), Some(DictError::ZeroSizedDict));
+        assert_eq!(Dictionary::<u8, u8>::try_with_capacity(usize::MAX).err(), Some(DictError::CapacityOverflow));
+        assert_eq!(Dictionary::try_from_vecs(vec![1, 2, 3], vec![4, 5]).err(),
+                   Some(DictError::VecLengthMismatch { expected: 3, got: 2 }));
+        assert_eq!(Dictionary::<u8, u8>::try_from_tuples(Vec::new()).err(), Some(DictError::ZeroSizedDict));
+
+        let _d = Dictionary::try_from_tuples(vec![(1, 2)]).unwrap();
+        assert_eq!(_d.try_get(&1), Ok(&2));
+        assert_eq!(_d.try_get(&3), Err(DictError::KeyNotFound));
+    }
+
+    #[test]
+    fn collect_reserves() {
+        let _d: Dictionary<u16, u16> = (0..100).map(|i| (i, i)).collect();
+
+        assert_eq!(_d.size(), 100);
+        assert_eq!(_d.capacity(), 256);
+    }
+
+    #[test]
+    fn into_tuples() {
+        let tuples: Vec<(u8, String)> = vec![(1, "a".to_string()), (2, "b".to_string())];
+        let mut round_trip = Dictionary::from_tuples(tuples.clone()).into_tuples();
+        round_trip.sort();
+
+        assert_eq!(round_trip, tuples);
+
+        let mut keys = create_dict().into_keys();
+        keys.sort();
+        assert_eq!(keys, vec![1, 2, 3, 4, 5]);
+
+        let mut values = create_dict().into_values();
+        values.sort();
+        assert_eq!(values, vec![0, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn hash_map_conversions() {
+        use std::collections::HashMap;
+
+        let map: HashMap<u8, u8> = vec![(1, 6), (2, 7), (3, 8), (4, 9), (5, 0)].into_iter().collect();
+        let _d = Dictionary::from(map.clone());
+
+        assert_eq!(_d, create_dict());
+        assert_eq!(_d, map);
+
+        let back: HashMap<u8, u8> = create_dict().into();
+        assert_eq!(back, map);
+        assert!(create_dict() != HashMap::new());
+    }
+
+    #[test]
+    fn intersection() {
+        let _d = create_dict();
+        let _o: Dictionary<u8, u8> = Dictionary::from_tuples(vec![(2, 7), (3, 1), (10, 10)]);
+        let both = _d.intersection(&_o);
+
+        assert_eq!(both.size(), 2);
+        assert_eq!(both[&2], (&7, &7));
+        assert_eq!(both[&3], (&8, &1));
+
+        let mut keys = _d.keys_intersection(&_o);
+        keys.sort();
+        assert_eq!(keys, vec![&2, &3]);
+
+        let same = _d.value_intersection(&_o);
+        assert_eq!(same.size(), 1);
+        assert_eq!(same.get(&2), Some(&7));
+    }
+
+    #[test]
+    fn difference() {
+        let _d = create_dict();
+        let _o: Dictionary<u8, u8> = Dictionary::from_tuples(vec![(2, 1), (3, 1), (10, 10)]);
+        let only_d = _d.difference(&_o);
+        let either = _d.symmetric_difference(&_o);
+
+        assert_eq!(only_d, Dictionary::from_tuples(vec![(1, 6), (4, 9), (5, 0)]));
+        assert_eq!(either, Dictionary::from_tuples(vec![(1, 6), (4, 9), (5, 0), (10, 10)]));
+        assert!(_d.difference(&_d).is_empty());
+    }
+
+    #[test]
+    fn union() {
+        let _d: Dictionary<&str, u8> = Dictionary::from_tuples(vec![("a", 1), ("b", 2)]);
+        let _o: Dictionary<&str, u8> = Dictionary::from_tuples(vec![("b", 5), ("c", 3)]);
+
+        let summed = _d.union(&_o, |a, b| a + b);
+        assert_eq!(summed, _o.union(&_d, |a, b| a + b));
+        assert_eq!(summed, Dictionary::from_tuples(vec![("a", 1), ("b", 7), ("c", 3)]));
+
+        let kept = _d.union(&_o, |a, _| *a);
+        assert_eq!(kept[&"b"], 2);
+        let replaced = _d.union(&_o, |_, b| *b);
+        assert_eq!(replaced[&"b"], 5);
+        assert_eq!(_d.size(), 2);
+    }
+
+    #[test]
+    fn for_each_and_fold() {
+        let _d = create_dict();
+        let mut total: u32 = 0;
+        _d.for_each(|k, v| total += (*k as u32) * (*v as u32));
+
+        assert_eq!(total, 6 + 14 + 24 + 36);
+        assert_eq!(_d.fold(0u32, |acc, _, v| acc + *v as u32), 30);
+        assert!(_d.any(|_, v| *v > 8));
+        assert!(!_d.any(|k, _| *k > 5));
+        assert!(_d.all(|k, _| *k > 0));
+        assert!(!_d.all(|_, v| *v > 0));
+    }
+
+    #[test]
+    fn filter() {
+        let _d = create_dict();
+        let evens = _d.filter(|k, _| k % 2 == 0);
+        let everything = _d.filter(|_, _| true);
+
+        assert_eq!(evens, Dictionary::from_tuples(vec![(2, 7), (4, 9)]));
+        assert_eq!(everything, _d);
+        assert_eq!(everything.capacity(), Dictionary::<u8, u8>::capacity_for(5));
+        assert_eq!(_d.size(), 5);
+
+        let labels = _d.filter_map(|_, v| if *v > 7 { Some(v.to_string()) } else { None });
+        assert_eq!(labels, Dictionary::from_tuples(vec![(3, "8".to_string()), (4, "9".to_string())]));
+    }
+
+    #[test]
+    fn count_where_and_find() {
+        let _d = create_dict();
+
+        assert_eq!(_d.count_where(|_, v| *v > 6), 3);
+        assert_eq!(_d.count_where(|k, _| *k > 5), 0);
+        assert_eq!(_d.find(|_, v| *v == 9), Some((&4, &9)));
+        assert_eq!(_d.find(|k, _| *k == 10), None);
+    }
+
+    #[test]
+    fn frequency_map() {
+        let words = "the cat and the hat and the bat".split(' ');
+        let _d = Dictionary::frequency_map(words);
+
+        assert_eq!(_d.size(), 5);
+        assert_eq!(_d[&"the"], 3);
+        assert_eq!(_d[&"and"], 2);
+        assert_eq!(_d[&"cat"], 1);
+        assert!(Dictionary::frequency_map(Vec::<u8>::new()).is_empty());
+    }
+
+    #[test]
+    fn lookup_by_value() {
+        let _d: Dictionary<u8, u8> = Dictionary::from_tuples(vec![(1, 6), (2, 6), (3, 7)]);
+        let mut keys = _d.get_all_keys_for_value(&6);
+        keys.sort();
+
+        assert_eq!(_d.lookup_by_value(&7), Some(&3));
+        assert!(_d.lookup_by_value(&6) == Some(&1) || _d.lookup_by_value(&6) == Some(&2));
+        assert_eq!(_d.lookup_by_value(&8), None);
+        assert_eq!(keys, vec![&1, &2]);
+    }
+
+    #[test]
+    fn rename_key() {
+        let mut _d = create_dict();
+
+        assert!(_d.rename_key(&1, 10));
+        assert_eq!(_d.get(&1), None);
+        assert_eq!(_d.get(&10), Some(&6));
+        assert_eq!(_d.size(), 5);
+
+        assert!(!_d.rename_key(&1, 11));
+        assert!(!_d.rename_key(&2, 3));
+        assert_eq!(_d.get(&2), Some(&7));
+        assert_eq!(_d.get(&3), Some(&8));
+        assert!(_d.verify_integrity().is_empty());
+    }
+
+    #[test]
+    fn swap_values() {
+        let mut _d = create_dict();
+
+        assert!(_d.swap_values(&1, &5));
+        assert_eq!(_d.get(&1), Some(&0));
+        assert_eq!(_d.get(&5), Some(&6));
+
+        assert!(_d.swap_values(&2, &2));
+        assert_eq!(_d.get(&2), Some(&7));
+
+        assert!(!_d.swap_values(&3, &10));
+        assert_eq!(_d.get(&3), Some(&8));
+        assert_eq!(_d.tombstone_count(), 0);
+    }
+
+    #[test]
+    fn pop() {
+        let mut _d = create_dict();
+        let mut popped: Vec<(u8, u8)> = Vec::new();
+        while let Some(entry) = _d.pop() {
+            popped.push(entry);
+        }
+        popped.sort();
+
+        assert_eq!(popped, vec![(1, 6), (2, 7), (3, 8), (4, 9), (5, 0)]);
+        assert!(_d.is_empty());
+        assert_eq!(_d.pop(), None);
+    }
+
+    #[test]
+    fn hash() {
+        use std::collections::HashSet;
+
+        let forwards: Dictionary<u8, u8> = (0..20).map(|i| (i, i * 2)).collect();
+        let mut backwards: Dictionary<u8, u8> = Dictionary::with_capacity(64);
+        for i in (0..20).rev() {
+            backwards.insert(i, i * 2);
+        }
+        let mut set: HashSet<Dictionary<u8, u8>> = HashSet::new();
+        set.insert(forwards);
+
+        assert!(set.contains(&backwards));
+        backwards.insert(0, 1);
+        assert!(!set.contains(&backwards));
+    }
+
+    #[test]
+    fn from_str() {
+        let _d: Dictionary<String, String> = "a:1, b : 2,c:x:y".parse().unwrap();
+
+        assert_eq!(_d.size(), 3);
+        assert_eq!(_d[&"b".to_string()], "2");
+        assert_eq!(_d[&"c".to_string()], "x:y");
+        assert_eq!(_d.to_compact_string().parse::<Dictionary<String, String>>(), Ok(_d));
+        assert!("".parse::<Dictionary<String, String>>().unwrap().is_empty());
+
+        assert_eq!("a:1,b".parse::<Dictionary<String, String>>().err(),
+                   Some(ParseDictError::MissingSeparator { pair: "b".to_string() }));
+        assert_eq!("a:1, :2".parse::<Dictionary<String, String>>().err(),
+                   Some(ParseDictError::EmptyKey { pair: " :2".to_string() }));
+    }
+
+    #[test]
+    fn fnv_hasher() {
+        use crate::fnv::{FnvBuildHasher, FnvHasher};
+        use std::hash::Hasher;
+
+        let mut hasher = FnvHasher::default();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+
+        let mut _d: Dictionary<u8, u8, FnvBuildHasher> = Dictionary::with_hasher(FnvBuildHasher::default());
+        _d.extend((0..50).map(|i| (i, i)));
+        assert_eq!(_d.get(&42), Some(&42));
+        assert!(_d.verify_integrity().is_empty());
+    }
+
+    #[test]
+    fn small_dict() {
+        let mut _s: SmallDict<u8, u8, 4> = SmallDict::new();
+        for i in 0..4 {
+            assert_eq!(_s.insert(i, i * 2), Ok(()));
+        }
+
+        assert!(_s.is_full());
+        assert_eq!(_s.insert(9, 9), Err((9, 9)));
+        assert_eq!(_s.insert(1, 5), Ok(()));
+        assert_eq!(_s.get(&1), Some(&5));
+        assert_eq!(_s.get(&9), None);
+
+        assert_eq!(_s.remove(&0), Some(0));
+        assert!(!_s.contains(&0));
+        assert_eq!(_s.insert(9, 9), Ok(()));
+
+        let _d: Dictionary<u8, u8> = _s.into();
+        assert_eq!(_d, Dictionary::from_tuples(vec![(1, 5), (2, 4), (3, 6), (9, 9)]));
+    }
+
+    #[test]
+    fn diff() {
+        let _d = create_dict();
+        let _o: Dictionary<u8, u8> = Dictionary::from_tuples(vec![(1, 6), (2, 1), (3, 8), (4, 9), (10, 10)]);
+        let diff = _d.diff(&_o);
+
+        assert_eq!(diff.added, vec![(&10, &10)]);
+        assert_eq!(diff.removed, vec![(&5, &0)]);
+        assert_eq!(diff.modified, vec![(&2, &7, &1)]);
+
+        let same = _d.diff(&_d);
+        assert!(same.added.is_empty() && same.removed.is_empty() && same.modified.is_empty());
+    }
+
+    #[test]
+    fn merge_with() {
+        let _d: Dictionary<&str, i32> = Dictionary::from_tuples(vec![("a", 1), ("b", 2)]);
+        let _o: Dictionary<&str, i32> = Dictionary::from_tuples(vec![("b", 5), ("c", 3)]);
+
+        let mut left = _d.clone();
+        left.merge_with(&_o, |_, a, b| *a.max(b));
+        let mut right = _o.clone();
+        right.merge_with(&_d, |_, a, b| *a.max(b));
+        assert_eq!(left, right);
+        assert_eq!(left, Dictionary::from_tuples(vec![("a", 1), ("b", 5), ("c", 3)]));
+
+        let mut subtracted = _d.clone();
+        subtracted.merge_with(&_o, |_, a, b| a - b);
+        assert_eq!(subtracted[&"b"], -3);
+        let mut reversed = _o.clone();
+        reversed.merge_with(&_d, |_, a, b| a - b);
+        assert_eq!(reversed[&"b"], 3);
+    }
+
+    #[test]
+    fn generation() {
+        let mut _d = create_dict();
+        let start = _d.snapshot_generation();
+
+        _d.insert(1, 100);
+        assert!(!_d.is_modified_since(start));
+        _d.remove(&10);
+        assert!(!_d.is_modified_since(start));
+
+        _d.insert(10, 10);
+        assert!(_d.is_modified_since(start));
+        let after_insert = _d.generation();
+        _d.remove(&10);
+        assert!(_d.is_modified_since(after_insert));
+        let after_remove = _d.generation();
+        _d.compact();
+        assert!(_d.is_modified_since(after_remove));
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let mut _d = create_dict();
+        let snapshot = _d.snapshot();
+        let copy = snapshot.clone();
+
+        _d.insert(1, 100);
+        _d.remove(&2);
+        _d.extend((10..30).map(|i| (i, i)));
+        _d.restore(snapshot);
+
+        assert_eq!(_d, create_dict());
+        assert_eq!(copy.size(), 5);
+        let mut other: Dictionary<u8, u8> = Dictionary::new();
+        other.restore(copy);
+        assert_eq!(other, _d);
+    }
+
+    #[test]
+    fn display_multibyte() {
+        let _e: Dictionary<&str, &str> = Dictionary::new();
+        let mut _d: Dictionary<&str, &str> = Dictionary::new();
+        _d.insert("鍵", "値🦀");
+
+        assert_eq!(format!("{}", _e), "{}");
+        assert_eq!(format!("{}", _d), "{鍵: 値🦀}");
+
+        _d.insert("🔑", "é");
+        let output = format!("{}", _d);
+        assert!(output == "{鍵: 値🦀, 🔑: é}" || output == "{🔑: é, 鍵: 値🦀}");
+    }
+
+    #[test]
+    fn to_sorted_vec() {
+        let _d = create_dict();
+
+        assert_eq!(_d.to_sorted_vec(), vec![(&1, &6), (&2, &7), (&3, &8), (&4, &9), (&5, &0)]);
+        assert_eq!(_d.to_sorted_vec_by(|a, b| b.1.cmp(a.1)),
+                   vec![(&4, &9), (&3, &8), (&2, &7), (&1, &6), (&5, &0)]);
+    }
+
+    #[test]
+    fn bucket_index() {
+        use crate::dictionary::bucket_index;
+
+        for n in [0usize, 7, 8, 1000, usize::MAX].iter() {
+            assert_eq!(bucket_index(*n, 8), n % 8);
+            assert_eq!(bucket_index(*n, 16), n % 16);
+        }
+
+        let mut _d: Dictionary<u16, u16> = Dictionary::new();
+        for i in 0..100 {
+            _d.insert(i, i);
+        }
+        assert!(_d.capacity().is_power_of_two());
+        assert!(_d.verify_integrity().is_empty());
+    }
+
+    #[test]
+    fn growth_factor_and_load_thresholds() {
+        // 8 * 1.5 = 12, rounded up to 16
+        let mut _d: Dictionary<u16, u16> = Dictionary::with_capacity(8).with_growth_factor(1.5);
+        for i in 0..7 {
+            _d.insert(i, i);
+        }
+        assert_eq!(_d.capacity(), 16);
+
+        let mut _l: Dictionary<u16, u16> = Dictionary::with_capacity(16).with_load_factor_threshold(0.9, 0.2);
+        for i in 0..14 {
+            _l.insert(i, i);
+        }
+        assert_eq!(_l.capacity(), 16);
+        _l.insert(14, 14);
+        assert!(_l.capacity() > 16);
+        assert!(_l.load_factor() <= 0.9);
+
+        let capacity = _l.capacity();
+        for i in 0..11 {
+            _l.remove(&i);
+        }
+        assert!(_l.capacity() < capacity);
+        assert_eq!(_l.size(), 4);
+        assert!(_l.verify_integrity().is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn bad_load_thresholds() {
+        let _d: Dictionary<u8, u8> = Dictionary::new().with_load_factor_threshold(1.0, 0.5);
+    }
+
+    #[test]
+    #[cfg(feature = "diagnostics")]
+    fn probe_lengths() {
+        let mut _d: Dictionary<u8, u8, Colliding> = Dictionary::with_capacity_and_hasher(12, Colliding::default());
+        assert_eq!(_d.max_probe_length(), 0);
+        assert_eq!(_d.average_probe_length(), 0.0);
+
+        for i in 0..4 {
+            _d.insert(i, i);
+        }
+        assert_eq!(_d.max_probe_length(), 3);
+        assert_eq!(_d.average_probe_length(), 1.5);
+    }
+
+    #[test]
+    #[cfg(feature = "diagnostics")]
+    fn hash_collisions() {
+        let mut _d: Dictionary<u8, u8, Identity> = Dictionary::with_capacity_and_hasher(16, Identity::default());
+        assert_eq!(_d.count_hash_collisions(), 0);
+
+        for i in [1, 2, 17, 33] {
+            _d.insert(i, i);
+        }
+        let distribution = _d.hash_distribution();
+        assert_eq!(distribution.len(), _d.capacity());
+        assert_eq!(distribution[1], 3);
+        assert_eq!(distribution[2], 1);
+        assert_eq!(distribution.iter().sum::<usize>(), _d.size());
+        assert_eq!(_d.count_hash_collisions(), 2);
+    }
+
+    #[test]
+    fn iter_buckets() {
+        let mut _d: Dictionary<u8, u8> = Dictionary::with_capacity(16);
+        for i in 0..4 {
+            _d.insert(i, i * 2);
+        }
+        _d.remove(&0);
+
+        let buckets: Vec<BucketView<u8, u8>> = _d.iter_buckets().collect();
+        assert_eq!(buckets.len(), _d.capacity());
+        let tombstones = buckets.iter().filter(|b| **b == BucketView::Tombstone).count();
+        assert_eq!(tombstones, _d.tombstone_count());
+        assert_eq!(buckets.iter().filter(|b| **b == BucketView::Empty).count(), _d.capacity() - _d.size() - tombstones);
+        for (i, b) in buckets.iter().enumerate() {
+            if let BucketView::Entry { key, value, index, .. } = b {
+                assert_eq!(**value, **key * 2);
+                assert_eq!(*index, i);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "robin-hood")]
+    fn robin_hood_insert() {
+        let mut _d: Dictionary<u8, u8, Identity> = Dictionary::with_capacity_and_hasher(16, Identity::default());
+        // 17 and 33 start in bucket 1 with 1, and push 2 out of bucket 2
+        for i in [2, 1, 17, 33] {
+            _d.insert(i, i);
+        }
+
+        let keys: Vec<Option<u8>> = _d.iter_buckets()
+            .map(|b| match b {
+                BucketView::Entry { key, .. } => Some(*key),
+                _ => None
+            })
+            .collect();
+        assert_eq!(keys[1..5], [Some(1), Some(17), Some(33), Some(2)]);
+        for i in [2, 1, 17, 33] {
+            assert_eq!(*_d.get(&i).unwrap(), i);
+        }
+        assert!(_d.verify_integrity().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "robin-hood")]
+    fn robin_hood_remove() {
+        let mut _d: Dictionary<u8, u8, Identity> = Dictionary::with_capacity_and_hasher(16, Identity::default());
+        for i in [2, 1, 17, 33] {
+            _d.insert(i, i);
+        }
+
+        // Everything after 1 moves back a bucket instead of leaving a tombstone
+        _d.remove(&1);
+        let keys: Vec<Option<u8>> = _d.iter_buckets()
+            .map(|b| match b {
+                BucketView::Entry { key, .. } => Some(*key),
+                _ => None
+            })
+            .collect();
+        assert_eq!(keys[1..5], [Some(17), Some(33), Some(2), None]);
+        assert_eq!(_d.tombstone_count(), 0);
+        assert!(_d.verify_integrity().is_empty());
+
+        // Mixed inserts and removals have to agree with a HashMap throughout
+        let mut _r: Dictionary<u16, u16> = Dictionary::new();
+        let mut map: std::collections::HashMap<u16, u16> = std::collections::HashMap::new();
+        let mut n: u32 = 1;
+        for _ in 0..5000 {
+            n = n.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let key = ((n >> 16) % 300) as u16;
+            if n & 1 == 0 {
+                assert_eq!(_r.remove(&key), map.remove(&key));
+            } else {
+                _r.insert(key, key);
+                map.insert(key, key);
+            }
+            assert_eq!(_r.size(), map.len());
+        }
+        assert_eq!(_r.tombstone_count(), 0);
+        for key in 0..300 {
+            assert_eq!(_r.get(&key), map.get(&key));
+        }
+        _r.retain(|k, _| k % 2 == 0);
+        map.retain(|k, _| k % 2 == 0);
+        for key in 0..300 {
+            assert_eq!(_r.get(&key), map.get(&key));
+        }
+    }
+
+    #[test]
+    fn ordered_dict() {
+        let mut _d: OrderedDictionary<u8, u8> = OrderedDictionary::new();
+        for i in [5, 3, 9, 1] {
+            _d.insert(i, i);
+        }
+        _d.insert(3, 30);
+        assert_eq!(_d.keys(), vec![&5, &3, &9, &1]);
+        assert_eq!(_d.get(&3), Some(&30));
+
+        assert_eq!(_d.remove(&9), Some(9));
+        assert_eq!(_d.remove(&9), None);
+        _d.insert(9, 9);
+        assert_eq!(_d.items(), vec![(&5, &5), (&3, &30), (&1, &1), (&9, &9)]);
+        assert_eq!(_d.size(), 4);
+
+        let mut _e: OrderedDictionary<u8, u8> = OrderedDictionary::new().with_move_to_end();
+        for i in [5, 3, 9, 1] {
+            _e.insert(i, i);
+        }
+        _e.insert(3, 30);
+        assert_eq!(_e.values(), vec![&5, &9, &1, &30]);
+
+        // Enough removals to pack the order down
+        for i in [5, 9, 1] {
+            _e.remove(&i);
+        }
+        for i in 10..14 {
+            _e.insert(i, i);
+        }
+        assert_eq!(_e.into_iter().collect::<Vec<(u8, u8)>>(), vec![(3, 30), (10, 10), (11, 11), (12, 12), (13, 13)]);
+    }
+
+    #[test]
+    fn multimap() {
+        let mut _m: Multimap<&str, u8> = vec![("a", 1), ("b", 2), ("a", 3), ("a", 1)].into_iter().collect();
+        assert_eq!(_m.get_all(&"a"), &[1, 3, 1]);
+        assert_eq!(_m.get_all(&"c"), &[] as &[u8]);
+        assert_eq!(_m.size(), 4);
+        assert_eq!(_m.key_count(), 2);
+        assert_eq!(_m.iter().filter(|(k, _)| **k == "a").count(), 3);
+
+        assert!(_m.remove_one(&"a", &1));
+        assert_eq!(_m.get_all(&"a"), &[3, 1]);
+        assert!(!_m.remove_one(&"a", &7));
+        assert!(_m.remove_one(&"b", &2));
+        assert!(!_m.contains(&"b"));
+
+        _m.insert("c", 4);
+        assert_eq!(_m.remove_all(&"a"), Some(vec![3, 1]));
+        assert_eq!(_m.remove_all(&"a"), None);
+        assert_eq!(_m.size(), 1);
+        assert_eq!(_m.iter().collect::<Vec<(&&str, &u8)>>(), vec![(&"c", &4)]);
+    }
+
+    #[test]
+    fn bidict() {
+        let mut _d: BiDict<&str, u8> = vec![("a", 1), ("b", 2)].into_iter().collect();
+        assert_eq!(_d.get_by_key(&"a"), Some(&1));
+        assert_eq!(_d.get_by_value(&2), Some(&"b"));
+        assert_eq!(_d.try_insert("c", 1), Err(("c", 1)));
+        assert_eq!(_d.try_insert("c", 3), Ok(()));
+
+        // Takes 1 from a and drops b's old pair
+        _d.insert("b", 1);
+        assert_eq!(_d.size(), 2);
+        assert_eq!(_d.get_by_value(&1), Some(&"b"));
+        assert_eq!(_d.get_by_key(&"a"), None);
+        assert!(!_d.contains_value(&2));
+
+        assert_eq!(_d.remove_by_value(&3), Some("c"));
+        assert!(!_d.contains_key(&"c"));
+        assert_eq!(_d.remove_by_key(&"b"), Some(1));
+        assert!(_d.is_empty());
+    }
+
+    #[test]
+    fn get_returns_reference() {
+        let mut _d: Dictionary<u8, String> = Dictionary::new();
+        _d.insert(1, "one".to_string());
+        assert_eq!(_d.get(&1).map(|s| s.as_str()), Some("one"));
+        assert_eq!(_d.get_cloned(&1), Some("one".to_string()));
+        assert_eq!(_d.get(&2), None);
+        assert_eq!(_d.get_cloned(&2), None);
+    }
+
+    #[test]
+    #[cfg(not(feature = "robin-hood"))] // Robin Hood tables never hold tombstones
+    fn get_mut_past_tombstones() {
+        // Every key shares one probe chain, and two removals stay above the
+        // shrink threshold so the tombstones are still there
+        let mut _d: Dictionary<u8, Vec<u8>, Colliding> = Dictionary::with_capacity_and_hasher(16, Colliding::default());
+        for i in 0..8 {
+            _d.insert(i, vec![i]);
+        }
+        _d.remove(&0);
+        _d.remove(&1);
+        assert_eq!(_d.capacity(), 16);
+        assert!(_d.tombstone_count() > 0);
+
+        for i in 2..8 {
+            _d.get_mut(&i).unwrap().push(i * 2);
+        }
+        for i in 2..8 {
+            assert_eq!(*_d.get(&i).unwrap(), vec![i, i * 2]);
+        }
+        assert!(_d.get_mut(&0).is_none());
+    }
+
+    #[test]
+    fn shrink_keeps_load_under_two_thirds() {
+        let mut _d: Dictionary<u8, u8> = Dictionary::with_capacity(16);
+        for i in 0..10 {
+            _d.insert(i, i);
+        }
+        for i in 0..8 {
+            _d.remove(&i);
+            assert!(_d.capacity() >= Dictionary::<u8, u8>::capacity_for(_d.size()));
+        }
+    }
+
+    #[test]
+    fn map_keys_keeps_load_thresholds() {
+        let mut _d: Dictionary<u8, u8> = Dictionary::with_capacity(16).with_load_factor_threshold(0.9, 0.05);
+        for i in 0..10 {
+            _d.insert(i, i);
+        }
+
+        // The default thresholds would shrink well before 2 entries are left
+        let mut _m: Dictionary<u8, u8> = _d.map_keys(|k| k + 1);
+        for i in 1..9 {
+            _m.remove(&i);
+        }
+        assert_eq!(_m.capacity(), 16);
+    }
 }
@@ -0,0 +1,120 @@
+use alloc::vec::Vec;
+use core::hash::Hash;
+use core::iter::FromIterator;
+
+use crate::dictionary::Dictionary;
+
+/* entries maps every key to the values stored under it, in the order they
+ *      were inserted. A key is only in entries while it has at least one
+ *      value
+ *
+ * size is the number of values across every key
+ *
+ * This is a dict where a key can hold more than one value, like HTTP
+ * headers or query parameters
+ */
+#[derive(Clone)]
+pub struct Multimap<K: Clone + Hash, V: Clone> {
+    entries: Dictionary<K, Vec<V>>,
+    size: usize
+}
+
+#[allow(dead_code)]
+impl<K: Clone + Hash + PartialEq, V: Clone> Multimap<K, V> {
+    pub fn new() -> Multimap<K, V> {
+        Multimap {
+            entries: Dictionary::new(),
+            size: 0
+        }
+    }
+
+    // Adds value to the values under key, keeping any already there
+    pub fn insert(&mut self, key: K, value: V) {
+        self.entries.entry(key).or_insert_with(Vec::new).push(value);
+        self.size += 1;
+    }
+
+    // Returns every value under key, empty if there are none
+    pub fn get_all(&self, key: &K) -> &[V] {
+        match self.entries.get_key_value(key) {
+            Some((_, values)) => values,
+            None => &[]
+        }
+    }
+
+    // Removes the first value under key equal to value, returning whether
+    // there was one
+    pub fn remove_one(&mut self, key: &K, value: &V) -> bool
+        where V: PartialEq {
+        let values = match self.entries.get_mut(key) {
+            Some(values) => values,
+            None => return false
+        };
+        let pos = match values.iter().position(|v| v == value) {
+            Some(pos) => pos,
+            None => return false
+        };
+
+        values.remove(pos);
+        if values.is_empty() {
+            self.entries.remove(key);
+        }
+        self.size -= 1;
+        true
+    }
+
+    // Removes key along with every value under it
+    pub fn remove_all(&mut self, key: &K) -> Option<Vec<V>> {
+        let values = self.entries.remove(key)?;
+        self.size -= values.len();
+        Some(values)
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.entries.contains(key)
+    }
+
+    // Number of values across every key
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn key_count(&self) -> usize {
+        self.entries.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    // Returns an iterator over (key, value) references, giving each key once
+    // for every value under it
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter()
+            .flat_map(|(k, values)| values.iter().map(move |v| (k, v)))
+    }
+
+    pub fn keys(&self) -> Vec<&K> {
+        self.entries.keys()
+    }
+
+    pub fn into_dictionary(self) -> Dictionary<K, Vec<V>> {
+        self.entries
+    }
+}
+
+impl<K: Clone + Hash + PartialEq, V: Clone> Default for Multimap<K, V> {
+    fn default() -> Multimap<K, V> {
+        Multimap::new()
+    }
+}
+
+impl<K: Clone + Hash + PartialEq, V: Clone> FromIterator<(K, V)> for Multimap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Multimap<K, V> {
+        let mut map = Multimap::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
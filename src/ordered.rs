@@ -0,0 +1,161 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::hash::Hash;
+use core::iter::FromIterator;
+
+use crate::dictionary::Dictionary;
+
+/* entries maps every key to its value and the index of its slot in order
+ *
+ * order holds the keys in the order they were inserted. Removing a key only
+ *      empties its slot, and the slots are packed back down once more than
+ *      half of them are empty
+ *
+ * move_to_end is whether inserting a key that is already there moves it to
+ *      the end of the order, like removing it and inserting it again, or
+ *      leaves it where it first went, like a Python dict
+ *
+ * This is a dict that iterates in insertion order, at the cost of an extra
+ * vec and one more lookup for every removal
+ */
+#[derive(Clone)]
+pub struct OrderedDictionary<K: Clone + Hash, V: Clone> {
+    entries: Dictionary<K, (V, usize)>,
+    order: Vec<Option<K>>,
+    move_to_end: bool
+}
+
+#[allow(dead_code)]
+impl<K: Clone + Hash + PartialEq, V: Clone> OrderedDictionary<K, V> {
+    pub fn new() -> OrderedDictionary<K, V> {
+        OrderedDictionary {
+            entries: Dictionary::new(),
+            order: Vec::new(),
+            move_to_end: false
+        }
+    }
+
+    // Makes inserting a key that is already there move it to the end of the
+    // order instead of keeping its first position
+    pub fn with_move_to_end(mut self) -> OrderedDictionary<K, V> {
+        self.move_to_end = true;
+        self
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if let Some(entry) = self.entries.get_mut(&key) {
+            entry.0 = value;
+            if !self.move_to_end {
+                return;
+            }
+            let old = entry.1;
+            entry.1 = self.order.len();
+            self.order[old] = None;
+            self.order.push(Some(key));
+            self.compact_order();
+            return;
+        }
+
+        self.entries.insert(key.clone(), (value, self.order.len()));
+        self.order.push(Some(key));
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get_key_value(key).map(|e| &e.1.0)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.entries.get_mut(key).map(|e| &mut e.0)
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (value, index) = self.entries.remove(key)?;
+        self.order[index] = None;
+        self.compact_order();
+        Some(value)
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.entries.contains(key)
+    }
+
+    pub fn size(&self) -> usize {
+        self.entries.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Returns an iterator over (key, value) references, in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.order.iter()
+            .flatten()
+            .filter_map(move |k| self.entries.get_key_value(k))
+            .map(|(k, v)| (k, &v.0))
+    }
+
+    // Returns a vector of the keys, in insertion order
+    pub fn keys(&self) -> Vec<&K> {
+        self.iter().map(|(k, _)| k).collect()
+    }
+
+    // Returns a vector of the values, in insertion order
+    pub fn values(&self) -> Vec<&V> {
+        self.iter().map(|(_, v)| v).collect()
+    }
+
+    // Returns a vector of (key, value) tuples, in insertion order
+    pub fn items(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+
+    pub fn into_dictionary(self) -> Dictionary<K, V> {
+        self.entries.into_iter().map(|(k, v)| (k, v.0)).collect()
+    }
+
+    // Packs the keys in order back down once more than half of its slots have
+    // been emptied, pointing every entry at its new slot
+    fn compact_order(&mut self) {
+        if self.order.len() <= 2 * self.entries.size() {
+            return;
+        }
+        self.order.retain(|k| k.is_some());
+        for (index, key) in self.order.iter().flatten().enumerate() {
+            if let Some(entry) = self.entries.get_mut(key) {
+                entry.1 = index;
+            }
+        }
+    }
+}
+
+impl<K: Clone + Hash + PartialEq, V: Clone> Default for OrderedDictionary<K, V> {
+    fn default() -> OrderedDictionary<K, V> {
+        OrderedDictionary::new()
+    }
+}
+
+impl<K: Clone + Hash + PartialEq, V: Clone> FromIterator<(K, V)> for OrderedDictionary<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> OrderedDictionary<K, V> {
+        let mut dict = OrderedDictionary::new();
+        for (key, value) in iter {
+            dict.insert(key, value);
+        }
+        dict
+    }
+}
+
+impl<K: Clone + Hash + PartialEq, V: Clone> IntoIterator for OrderedDictionary<K, V> {
+    type Item = (K, V);
+    type IntoIter = vec::IntoIter<(K, V)>;
+
+    // Puts every entry back in its slot of the order, then drops the
+    // emptied slots
+    fn into_iter(self) -> vec::IntoIter<(K, V)> {
+        let mut slots: Vec<Option<(K, V)>> = vec![None; self.order.len()];
+        for (key, (value, index)) in self.entries {
+            slots[index] = Some((key, value));
+        }
+        slots.into_iter().flatten().collect::<Vec<(K, V)>>().into_iter()
+    }
+}
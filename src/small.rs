@@ -0,0 +1,147 @@
+use core::hash::{BuildHasher, Hash};
+
+use crate::dictionary::{probe_step, Bucket, DefaultHashBuilder, Dictionary, PERTURB_SHIFT};
+
+/* table is a fixed array of N buckets, probed the same way as a Dictionary's
+ *
+ * size is the number of entries in the table
+ *
+ * hash_builder makes the hasher every key is hashed with
+ *
+ * This is a dict for maps known to never hold more than N entries. The
+ * table never resizes, so it stays wherever the SmallDict itself lives and
+ * nothing is allocated on the heap. N must be at least 1, which is checked
+ * when the SmallDict is made
+ */
+pub struct SmallDict<K: Clone + Hash, V: Clone, const N: usize> {
+    table: [Bucket<K, V>; N],
+    size: usize,
+    hash_builder: DefaultHashBuilder
+}
+
+#[allow(dead_code)]
+impl<K: Clone + Hash + PartialEq, V: Clone, const N: usize> SmallDict<K, V, N> {
+    const NONZERO: () = assert!(N > 0, "Cannot create a zero-sized dict");
+
+    // Once perturb has been shifted down to zero the probing goes linear, so
+    // this many steps are always enough to visit every bucket
+    const MAX_PROBES: usize = (usize::BITS as usize) / (PERTURB_SHIFT as usize) + 1 + N;
+
+    pub fn new() -> SmallDict<K, V, N> {
+        let () = Self::NONZERO;
+        SmallDict {
+            table: [(); N].map(|_| Bucket::Empty),
+            size: 0,
+            hash_builder: DefaultHashBuilder::default()
+        }
+    }
+
+    /* Like Dictionary::probe(), returns Ok(index) of the bucket holding key
+     * if it is there and Err(Some(index)) of the bucket a new entry for it
+     * should go in otherwise. The table can fill up completely, so probing
+     * gives up with Err(None) after MAX_PROBES steps
+     */
+    fn probe(&self, key: &K, key_hash: usize) -> Result<usize, Option<usize>> {
+        let mut index = key_hash % N;
+        let mut perturb: usize = key_hash;
+        let mut first_tombstone: Option<usize> = None;
+
+        for _ in 0..Self::MAX_PROBES {
+            match &self.table[index] {
+                Bucket::Entry(d) if d.0 == *key => return Ok(index),
+                Bucket::Entry(_) => (),
+                Bucket::Tombstone => {
+                    first_tombstone.get_or_insert(index);
+                },
+                Bucket::Empty => return Err(Some(first_tombstone.unwrap_or(index)))
+            }
+            // N need not be a power of two, so this wraps with a modulo
+            index = probe_step(index, &mut perturb) % N;
+        }
+
+        Err(first_tombstone)
+    }
+
+    fn get_hash(&self, key: &K) -> usize {
+        self.hash_builder.hash_one(key) as usize
+    }
+
+    // Inserts or overwrites key, handing the pair back if the table is full
+    pub fn insert(&mut self, key: K, value: V) -> Result<(), (K, V)> {
+        let hash = self.get_hash(&key);
+        match self.probe(&key, hash) {
+            Ok(index) => {
+                if let Bucket::Entry(d) = &mut self.table[index] {
+                    d.1 = value;
+                }
+                Ok(())
+            },
+            Err(Some(index)) => {
+                self.table[index] = Bucket::Entry((key, value, hash, index));
+                self.size += 1;
+                Ok(())
+            },
+            Err(None) => Err((key, value))
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        match self.probe(key, self.get_hash(key)) {
+            Ok(index) => match &self.table[index] {
+                Bucket::Entry(d) => Some(&d.1),
+                _ => None
+            },
+            Err(_) => None
+        }
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    // Removes key, returning its value. Leaves a tombstone like Dictionary does
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let index = self.probe(key, self.get_hash(key)).ok()?;
+        self.size -= 1;
+        match core::mem::replace(&mut self.table[index], Bucket::Tombstone) {
+            Bucket::Entry(d) => Some(d.1),
+            _ => None
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.size == N
+    }
+}
+
+impl<K: Clone + Hash + PartialEq, V: Clone, const N: usize> Default for SmallDict<K, V, N> {
+    fn default() -> SmallDict<K, V, N> {
+        SmallDict::new()
+    }
+}
+
+impl<K: Clone + Hash + PartialEq, V: Clone, const N: usize> From<SmallDict<K, V, N>> for Dictionary<K, V> {
+    fn from(small: SmallDict<K, V, N>) -> Dictionary<K, V> {
+        let mut dict: Dictionary<K, V> = Dictionary::with_capacity(Dictionary::<K, V>::capacity_for(small.size));
+        for bucket in small.table {
+            if let Bucket::Entry(d) = bucket {
+                dict.insert(d.0, d.1);
+            }
+        }
+        dict
+    }
+}
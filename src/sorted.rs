@@ -0,0 +1,44 @@
+use alloc::collections::BTreeMap;
+use core::hash::Hash;
+use core::ops::Deref;
+
+use crate::dictionary::Dictionary;
+
+/* inner holds the entries of a dict sorted by key
+ *
+ * This is a read-only sorted view of a dict, made by moving its entries
+ * into a BTreeMap. It derefs to the map for iterating in key order and can
+ * be turned back into a dict when done
+ */
+pub struct SortedDictionary<K: Clone + Hash + Ord, V: Clone> {
+    inner: BTreeMap<K, V>
+}
+
+#[allow(dead_code)]
+impl<K: Clone + Hash + Ord, V: Clone> SortedDictionary<K, V> {
+    pub fn into_inner(self) -> BTreeMap<K, V> {
+        self.inner
+    }
+
+    pub fn into_dictionary(self) -> Dictionary<K, V> {
+        let mut dict: Dictionary<K, V> = Dictionary::new();
+        for (key, value) in self.inner {
+            dict.insert(key, value);
+        }
+        dict
+    }
+}
+
+impl<K: Clone + Hash + Ord, V: Clone> Deref for SortedDictionary<K, V> {
+    type Target = BTreeMap<K, V>;
+
+    fn deref(&self) -> &BTreeMap<K, V> {
+        &self.inner
+    }
+}
+
+impl<K: Clone + Hash + Ord, V: Clone> From<BTreeMap<K, V>> for SortedDictionary<K, V> {
+    fn from(inner: BTreeMap<K, V>) -> SortedDictionary<K, V> {
+        SortedDictionary { inner }
+    }
+}
@@ -0,0 +1,112 @@
+use alloc::vec::Vec;
+use core::cell::OnceCell;
+use core::hash::Hash;
+
+#[cfg(feature = "rand")]
+use rand::Rng;
+
+use crate::dictionary::Dictionary;
+
+/* weights maps every key to its weight, which must be a finite number
+ *      that is zero or greater
+ *
+ * cumulative is a running total of the weights in table order, paired with
+ *      the table index each key lives at. It is built the first time a sample
+ *      is taken and thrown away whenever the weights change, so sampling is a
+ *      binary search over it
+ *
+ * This is meant for picking keys at random, with each key's chance of being
+ * picked proportional to its weight
+ */
+pub struct WeightedDictionary<K: Clone + Hash> {
+    weights: Dictionary<K, f64>,
+    cumulative: OnceCell<Vec<(usize, f64)>>
+}
+
+#[allow(dead_code)]
+impl<K: Clone + Hash + PartialEq> WeightedDictionary<K> {
+    pub fn new() -> WeightedDictionary<K> {
+        WeightedDictionary {
+            weights: Dictionary::new(),
+            cumulative: OnceCell::new()
+        }
+    }
+
+    // Sets the weight of key, replacing any previous weight
+    pub fn insert(&mut self, key: K, weight: f64) {
+        if !weight.is_finite() || weight < 0.0 {
+            panic!("Weights must be finite and non-negative");
+        }
+        self.weights.insert(key, weight);
+        self.cumulative.take();
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<f64> {
+        let output = self.weights.remove(key);
+        self.cumulative.take();
+        output
+    }
+
+    pub fn weight(&self, key: &K) -> Option<f64> {
+        self.weights.get_cloned(key)
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.weights.contains(key)
+    }
+
+    pub fn size(&self) -> usize {
+        self.weights.size()
+    }
+
+    pub fn total_weight(&self) -> f64 {
+        match self.cumulative().last() {
+            Some(last) => last.1,
+            None => 0.0
+        }
+    }
+
+    // Picks a key using u, a number in [0, 1), as the source of randomness.
+    // Returns None if the dict is empty, every weight is zero or u is out
+    // of range
+    pub fn sample_with(&self, u: f64) -> Option<&K> {
+        // A negative u would land before the first key whatever its weight
+        if !(0.0..1.0).contains(&u) {
+            return None;
+        }
+        let cumulative = self.cumulative();
+        let total = cumulative.last()?.1;
+        if total <= 0.0 {
+            return None;
+        }
+
+        let target = u * total;
+        // u * total can round up to total, in which case the pick falls back
+        // to the first key that brings the running total up to total
+        let pos = cumulative.partition_point(|c| c.1 <= target)
+            .min(cumulative.partition_point(|c| c.1 < total));
+        let index = cumulative[pos].0;
+        self.weights.entry_at(index).map(|e| e.0)
+    }
+
+    // Picks a key at random, with probability proportional to its weight
+    #[cfg(feature = "rand")]
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Option<&K> {
+        self.sample_with(rng.gen::<f64>())
+    }
+
+    // Builds the cumulative weights if they were thrown away since the last sample
+    fn cumulative(&self) -> &Vec<(usize, f64)> {
+        self.cumulative.get_or_init(|| {
+            let mut running = 0.0;
+            let mut output: Vec<(usize, f64)> = Vec::with_capacity(self.weights.size());
+            for index in 0..self.weights.capacity() {
+                if let Some((_, weight)) = self.weights.entry_at(index) {
+                    running += *weight;
+                    output.push((index, running));
+                }
+            }
+            output
+        })
+    }
+}